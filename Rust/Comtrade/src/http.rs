@@ -0,0 +1,42 @@
+//! Request helpers shared with the `tradingeconomics` crate, which keeps its
+//! endpoints on the same conventions.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::error::Error;
+
+pub fn get_json<T: DeserializeOwned>(url: String) -> Result<T, Box<dyn Error + Send + Sync>> {
+    let resp = reqwest::blocking::get(url)?
+        .error_for_status()?
+        .json::<T>()?;
+    Ok(resp)
+}
+
+/// Percent-encodes a path segment or query value; country, category and
+/// indicator names contain spaces, commas and ampersands.
+pub fn encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for b in segment.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Accepts identifiers that the API sends either as strings or as numbers
+/// (FIPS codes, category ids).
+pub fn opt_string_or_number<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(match serde_json::Value::deserialize(d)? {
+        serde_json::Value::String(s) => Some(s),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+pub fn string_or_number<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    opt_string_or_number(d)?.ok_or_else(|| serde::de::Error::custom("missing id"))
+}
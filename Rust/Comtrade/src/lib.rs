@@ -1,73 +1,276 @@
+use http::{encode, get_json, opt_string_or_number, string_or_number};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[doc(hidden)]
+pub mod http;
+pub mod matrix;
+pub mod network;
+pub mod symbol;
+
+pub use matrix::{TradeCell, TradeMatrix};
+pub use network::{Centrality, Exposure, TradeNetwork};
+pub use symbol::{CategoryTree, ComtradeSymbol};
+
+/// Upper bound on the pages `get_comtrade_between_two_countries_all` walks.
+const MAX_PAGES: u32 = 200;
+
+/// Direction of a trade flow, as used by the `type` filter of the
+/// `/comtrade` endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FlowType {
+    Import,
+    Export,
+}
+
+impl FlowType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlowType::Import => "import",
+            FlowType::Export => "export",
+        }
+    }
+}
+
+impl FromStr for FlowType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "import" | "imports" => Ok(FlowType::Import),
+            "export" | "exports" => Ok(FlowType::Export),
+            other => Err(format!("unknown flow type: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for FlowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One node of the `/comtrade/categories` tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComtradeCategory {
+    #[serde(deserialize_with = "string_or_number")]
+    pub id: String,
+    pub name: String,
+    #[serde(default, deserialize_with = "opt_string_or_number")]
+    pub parent_id: Option<String>,
+    #[serde(default, rename = "pretty_name")]
+    pub pretty_name: Option<String>,
+}
+
+/// One entry of `/comtrade/countries`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComtradeCountry {
+    #[serde(default, deserialize_with = "opt_string_or_number")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub subregion: Option<String>,
+    #[serde(default)]
+    pub iso: Option<String>,
+}
+
+/// One trade-flow observation as returned by the `/comtrade` endpoints.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComtradeFlow {
+    pub symbol: String,
+    pub date: String,
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub country1: Option<String>,
+    #[serde(default)]
+    pub country2: Option<String>,
+    #[serde(default, rename = "type")]
+    pub flow_type: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+}
+
+impl ComtradeFlow {
+    /// The flow direction, when the record carries a recognisable `type`.
+    pub fn flow(&self) -> Option<FlowType> {
+        self.flow_type.as_deref().and_then(|t| t.parse().ok())
+    }
+
+    /// The decoded `symbol`, when it follows the Comtrade symbol layout.
+    pub fn decoded_symbol(&self) -> Option<ComtradeSymbol> {
+        self.symbol.parse().ok()
+    }
+}
+
+/// One entry of `/comtrade/updates`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComtradeUpdate {
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
 
 pub fn get_comtrade_categories(
     base_url: String,
     client_key: String,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+) -> Result<Vec<ComtradeCategory>, Box<dyn Error + Send + Sync>> {
     let path = String::from("/comtrade/categories");
     let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    println!("-----------------------Get detailed information about comtrade main categories----------------------");
-    println!("{:#?}", resp);
-    Ok(())
+    get_json(url)
 }
 
 pub fn get_comtrade_countries(
     base_url: String,
     client_key: String,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+) -> Result<Vec<ComtradeCountry>, Box<dyn Error + Send + Sync>> {
     let path = String::from("/comtrade/countries");
     let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    println!("-----------------------Get detailed information about comtrade countries----------------------");
-    println!("{:#?}", resp);
-    Ok(())
+    get_json(url)
 }
 
+/// Snapshot of every flow reported by `country`, optionally only imports or
+/// exports.
 pub fn get_comtrade_by_country(
     base_url: String,
     client_key: String,
-) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
-    let path = String::from("/comtrade/country/sweden");
-    let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    Ok(resp)
+    country: &str,
+    flow: Option<FlowType>,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/comtrade/country/{}", encode(country));
+    let url = format!("{}{}?c={}{}", base_url, path, client_key, type_param(flow));
+    get_json(url)
 }
 
 pub fn get_comtrade_by_country_page(
     base_url: String,
     client_key: String,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let path = String::from("/comtrade/country");
-    let params = String::from("/sweden");
-    let page = String::from("/2");
-    let url = format!("{}{}{}{}?c={}", base_url, path, params, page, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    println!("-----------------------Get comtrade data by specific country and page----------------------");
-    println!("{:#?}", resp);
-    Ok(())
+    country: &str,
+    page: u32,
+    flow: Option<FlowType>,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/comtrade/country/{}/{}", encode(country), page);
+    let url = format!("{}{}?c={}{}", base_url, path, client_key, type_param(flow));
+    get_json(url)
 }
 
+/// Flows between `country1` and `country2`. Pages start at 1; `None` asks
+/// for the first page.
 pub fn get_comtrade_between_two_countries(
     base_url: String,
     client_key: String,
-) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
-    let path = String::from("/comtrade/country/mexico/sweden/1");
+    country1: &str,
+    country2: &str,
+    page: Option<u32>,
+    flow: Option<FlowType>,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let mut path = format!(
+        "/comtrade/country/{}/{}",
+        encode(country1),
+        encode(country2)
+    );
+    if let Some(page) = page {
+        path = format!("{}/{}", path, page);
+    }
+    let url = format!("{}{}?c={}{}", base_url, path, client_key, type_param(flow));
+    get_json(url)
+}
+
+/// Walks every page of the bilateral endpoint. An empty page, or one
+/// repeating the previous page, ends the listing; more than `MAX_PAGES`
+/// pages (200) is an error rather than a silently truncated result.
+pub fn get_comtrade_between_two_countries_all(
+    base_url: String,
+    client_key: String,
+    country1: &str,
+    country2: &str,
+    flow: Option<FlowType>,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let mut flows = Vec::new();
+    let mut last_first: Option<String> = None;
+    for page in 1..=MAX_PAGES {
+        let batch = get_comtrade_between_two_countries(
+            base_url.clone(),
+            client_key.clone(),
+            country1,
+            country2,
+            Some(page),
+            flow,
+        )?;
+        let first = batch.first().map(|f| f.symbol.clone());
+        if first.is_none() || first == last_first {
+            return Ok(flows);
+        }
+        last_first = first;
+        flows.extend(batch);
+    }
+    Err(format!(
+        "{} / {}: more than {} pages of flows",
+        country1, country2, MAX_PAGES
+    )
+    .into())
+}
+
+/// Imports or exports of `country`, either by main category or, when
+/// `category` is given, within that category.
+pub fn get_comtrade_by_type(
+    base_url: String,
+    client_key: String,
+    country: &str,
+    flow: FlowType,
+    category: Option<&str>,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let mut path = format!("/comtrade/{}/{}", flow, encode(country));
+    if let Some(category) = category {
+        path = format!("{}/{}", path, encode(category));
+    }
     let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    Ok(resp)
+    get_json(url)
+}
+
+pub fn get_comtrade_totals(
+    base_url: String,
+    client_key: String,
+    country: &str,
+    flow: FlowType,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/comtrade/{}/{}/totals", flow, encode(country));
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
 }
 
 pub fn get_comtrade_historical(
     base_url: String,
     client_key: String,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let path = String::from("/comtrade/historical/PRTESP24031");
+    symbol: &str,
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/comtrade/historical/{}", encode(symbol));
     let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
-    println!("-----------------------Get comtrade historical data by symbol----------------------");
-    println!("{:#?}", resp);
-    Ok(())
+    get_json(url)
+}
+
+pub fn get_comtrade_updates(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<ComtradeUpdate>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/comtrade/updates");
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
 }
 
 pub fn get_crude_oil_historical(
@@ -79,3 +282,10 @@ pub fn get_crude_oil_historical(
     let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
     Ok(resp)
 }
+
+fn type_param(flow: Option<FlowType>) -> String {
+    match flow {
+        Some(flow) => format!("&type={}", flow),
+        None => String::new(),
+    }
+}
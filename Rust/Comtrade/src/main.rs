@@ -1,91 +1,46 @@
+use comtrade::*;
 use std::error::Error;
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let client = "guest:guest";
     let base = "http://api.tradingeconomics.com";
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-	get_comtrade_categories(base_url, client_key);
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-    get_comtrade_Countries(base_url, client_key);
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-    get_comtrade_by_country(base_url, client_key);
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-    get_comtrade_by_country_page(base_url, client_key);
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-    get_comtrade_between_two_contries(base_url, client_key);
-    let client_key:String  = String::from(client);
-    let base_url:String = String::from(base);
-    get_comtrade_Historical(base_url, client_key);
-}
+    let client_key: String = String::from(client);
+    let base_url: String = String::from(base);
 
-fn get_comtrade_categories(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/categories");
-    let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp = get_comtrade_categories(base_url.clone(), client_key.clone())?;
     println!("-----------------------Get detailed information about comtrade main categories----------------------");
     println!("{:#?}", resp);
-    Ok(())
-
-}
 
-fn get_comtrade_Countries(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/countries");
-    let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp = get_comtrade_countries(base_url.clone(), client_key.clone())?;
     println!("-----------------------Get detailed information about comtrade countries----------------------");
     println!("{:#?}", resp);
-    Ok(())
 
-}
-
-
-fn get_comtrade_by_country(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/country");
-    let params:String = String::from("/sweden");
-    let url = format!("{}{}{}?c={}", base_url, path, params, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp = get_comtrade_by_country(base_url.clone(), client_key.clone(), "sweden", None)?;
     println!("-----------------------Get detailed information about comtrade per country----------------------");
     println!("{:#?}", resp);
-    Ok(())
-
-}
 
-fn get_comtrade_by_country_page(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/country");
-    let params:String = String::from("/sweden");
-    let page:String = String::from("/2");
-    let url = format!("{}{}{}{}?c={}", base_url, path, params, page, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp =
+        get_comtrade_by_country_page(base_url.clone(), client_key.clone(), "sweden", 2, None)?;
     println!("-----------------------Get comtrade data by specific country and page----------------------");
     println!("{:#?}", resp);
-    Ok(())
-
-}
 
-fn get_comtrade_between_two_contries(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/country");
-    let params1:String = String::from("/mexico");
-    let params2:String = String::from("/sweden");
-    let page:String = String::from("/2");
-    let url = format!("{}{}{}{}{}?c={}", base_url, path, params1, params2, page, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp = get_comtrade_between_two_countries(
+        base_url.clone(),
+        client_key.clone(),
+        "mexico",
+        "sweden",
+        Some(2),
+        None,
+    )?;
     println!("-----------------------Get comtrade data between countries and page number----------------------");
     println!("{:#?}", resp);
-    Ok(())
-
-}
 
-fn get_comtrade_Historical(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/comtrade/historical/PRTESP24031");
-    let url = format!("{}{}?c={}", base_url, path, client_key);
-    let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
+    let resp = get_comtrade_historical(base_url.clone(), client_key.clone(), "PRTESP24031")?;
     println!("-----------------------Get comtrade historical data by symbol----------------------");
     println!("{:#?}", resp);
-    Ok(())
 
-}
\ No newline at end of file
+    let resp = get_comtrade_updates(base_url, client_key)?;
+    println!("-----------------------Get the latest comtrade updates----------------------");
+    println!("{:#?}", resp);
+    Ok(())
+}
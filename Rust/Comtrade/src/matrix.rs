@@ -0,0 +1,282 @@
+//! N×N bilateral trade matrix for one commodity category and year.
+//!
+//! The matrix is built from `/comtrade/country/{a}/{b}` pages and the
+//! `/comtrade/historical/{symbol}` series of the flows they list. Row `a`,
+//! column `b` holds what `a` reports exporting to and importing from `b`,
+//! along with the derived balance, the pair's share of `a`'s trade within the
+//! matrix, and growth against the previous year.
+
+use crate::{
+    get_comtrade_between_two_countries_all, get_comtrade_historical, ComtradeFlow, FlowType,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TradeCell {
+    pub exports: f64,
+    pub imports: f64,
+    /// `exports - imports`.
+    pub balance: f64,
+    /// The pair's exports plus imports over the reporter's total with every
+    /// partner in the matrix.
+    pub share_of_total: f64,
+    pub exports_growth: Option<f64>,
+    pub imports_growth: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeMatrix {
+    pub category: String,
+    pub year: i32,
+    pub countries: Vec<String>,
+    cells: Vec<Option<TradeCell>>,
+}
+
+impl TradeMatrix {
+    /// Fetches every ordered pair of `countries`, with the previous year for
+    /// growth, and builds the matrix.
+    pub fn fetch(
+        base_url: String,
+        client_key: String,
+        countries: &[&str],
+        category: &str,
+        year: i32,
+    ) -> Result<TradeMatrix, Box<dyn Error + Send + Sync>> {
+        let flows =
+            fetch_category_flows(base_url, client_key, countries, category, &[year - 1, year])?;
+        Ok(TradeMatrix::from_flows(countries, category, year, &flows))
+    }
+
+    /// Builds the matrix from already fetched flows. `category` matches the
+    /// flow's category name or the commodity code of its symbol. Growth is
+    /// only filled in where `flows` also carry records for the previous year.
+    pub fn from_flows(
+        countries: &[&str],
+        category: &str,
+        year: i32,
+        flows: &[ComtradeFlow],
+    ) -> TradeMatrix {
+        let n = countries.len();
+        let mut current = vec![(0.0, 0.0); n * n];
+        let mut previous = vec![(0.0, 0.0); n * n];
+        let mut seen = vec![false; n * n];
+        let mut seen_previous = vec![false; n * n];
+        let index = |name: &str| countries.iter().position(|c| c.eq_ignore_ascii_case(name));
+
+        for flow in flows.iter().filter(|f| matches_category(f, category)) {
+            let (Some(reporter), Some(partner)) = (
+                flow.country1.as_deref().and_then(index),
+                flow.country2.as_deref().and_then(index),
+            ) else {
+                continue;
+            };
+            let (Some(kind), Some(value), Some(flow_year)) =
+                (flow.flow(), flow.value, flow_year(flow))
+            else {
+                continue;
+            };
+            let cell = reporter * n + partner;
+            let (bucket, seen) = if flow_year == year {
+                (&mut current[cell], &mut seen[cell])
+            } else if flow_year == year - 1 {
+                (&mut previous[cell], &mut seen_previous[cell])
+            } else {
+                continue;
+            };
+            match kind {
+                FlowType::Export => bucket.0 += value,
+                FlowType::Import => bucket.1 += value,
+            }
+            *seen = true;
+        }
+
+        let mut cells = vec![None; n * n];
+        for reporter in 0..n {
+            let row = &current[reporter * n..(reporter + 1) * n];
+            let total: f64 = row.iter().map(|(e, i)| e + i).sum();
+            for partner in 0..n {
+                let idx = reporter * n + partner;
+                if !seen[idx] {
+                    continue;
+                }
+                let (exports, imports) = current[idx];
+                let (prev_exports, prev_imports) = previous[idx];
+                cells[idx] = Some(TradeCell {
+                    exports,
+                    imports,
+                    balance: exports - imports,
+                    share_of_total: if total > 0.0 {
+                        (exports + imports) / total
+                    } else {
+                        0.0
+                    },
+                    exports_growth: growth(seen_previous[idx], prev_exports, exports),
+                    imports_growth: growth(seen_previous[idx], prev_imports, imports),
+                });
+            }
+        }
+
+        TradeMatrix {
+            category: category.to_string(),
+            year,
+            countries: countries.iter().map(|c| c.to_string()).collect(),
+            cells,
+        }
+    }
+
+    pub fn get(&self, reporter: &str, partner: &str) -> Option<&TradeCell> {
+        let r = self.position(reporter)?;
+        let p = self.position(partner)?;
+        self.cells[r * self.countries.len() + p].as_ref()
+    }
+
+    /// Non-empty cells as `(reporter, partner, cell)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &TradeCell)> {
+        let n = self.countries.len();
+        self.cells.iter().enumerate().filter_map(move |(i, cell)| {
+            cell.as_ref().map(|c| {
+                (
+                    self.countries[i / n].as_str(),
+                    self.countries[i % n].as_str(),
+                    c,
+                )
+            })
+        })
+    }
+
+    /// A reporter's total exports minus imports across the matrix.
+    pub fn trade_balance(&self, reporter: &str) -> Option<f64> {
+        self.position(reporter)?;
+        Some(
+            self.iter()
+                .filter(|(r, _, _)| r.eq_ignore_ascii_case(reporter))
+                .map(|(_, _, c)| c.balance)
+                .sum(),
+        )
+    }
+
+    /// Long-format CSV, one row per non-empty pair.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "reporter,partner,category,year,exports,imports,balance,share_of_total,exports_growth,imports_growth\n",
+        );
+        for (reporter, partner, c) in self.iter() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(reporter),
+                csv_field(partner),
+                csv_field(&self.category),
+                self.year,
+                c.exports,
+                c.imports,
+                c.balance,
+                c.share_of_total,
+                c.exports_growth.map(|g| g.to_string()).unwrap_or_default(),
+                c.imports_growth.map(|g| g.to_string()).unwrap_or_default(),
+            );
+        }
+        out
+    }
+
+    fn position(&self, country: &str) -> Option<usize> {
+        self.countries
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(country))
+    }
+}
+
+/// Flows in `category` between every ordered pair of `countries`, one per
+/// symbol and year in `years`. The bilateral pages only carry each symbol's
+/// latest observation, so each symbol's history is fetched as well.
+pub(crate) fn fetch_category_flows(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+    category: &str,
+    years: &[i32],
+) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+    let mut latest = Vec::new();
+    for reporter in countries {
+        for partner in countries {
+            if reporter.eq_ignore_ascii_case(partner) {
+                continue;
+            }
+            latest.extend(
+                get_comtrade_between_two_countries_all(
+                    base_url.clone(),
+                    client_key.clone(),
+                    reporter,
+                    partner,
+                    None,
+                )?
+                .into_iter()
+                .filter(|f| matches_category(f, category)),
+            );
+        }
+    }
+
+    // One record per symbol and year; history rows take the pair, type
+    // and category of the symbol's latest record, which they may omit.
+    let mut by_year: BTreeMap<(String, i32), ComtradeFlow> = BTreeMap::new();
+    let mut symbols = BTreeSet::new();
+    for flow in latest {
+        if !symbols.insert(flow.symbol.clone()) {
+            continue;
+        }
+        let history = get_comtrade_historical(base_url.clone(), client_key.clone(), &flow.symbol)?;
+        for row in history {
+            let Some(row_year) = flow_year(&row) else {
+                continue;
+            };
+            if years.contains(&row_year) {
+                by_year.insert(
+                    (flow.symbol.clone(), row_year),
+                    ComtradeFlow {
+                        date: row.date,
+                        value: row.value,
+                        last_update: row.last_update,
+                        ..flow.clone()
+                    },
+                );
+            }
+        }
+        if let Some(latest_year) = flow_year(&flow).filter(|y| years.contains(y)) {
+            by_year
+                .entry((flow.symbol.clone(), latest_year))
+                .or_insert(flow);
+        }
+    }
+    Ok(by_year.into_values().collect())
+}
+
+pub(crate) fn matches_category(flow: &ComtradeFlow, category: &str) -> bool {
+    flow.category
+        .as_deref()
+        .is_some_and(|c| c.eq_ignore_ascii_case(category))
+        || flow
+            .decoded_symbol()
+            .is_some_and(|s| s.commodity == category)
+}
+
+fn flow_year(flow: &ComtradeFlow) -> Option<i32> {
+    flow.date.get(..4)?.parse().ok()
+}
+
+fn growth(had_previous: bool, previous: f64, current: f64) -> Option<f64> {
+    if had_previous && previous != 0.0 {
+        Some(current / previous - 1.0)
+    } else {
+        None
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,249 @@
+//! Directed, weighted trade network built from Comtrade partner data.
+//!
+//! An edge `a -> b` carries the value of goods shipped from `a` to `b`. The
+//! exporter's own report is used when present; otherwise the importer's
+//! mirror report fills the gap, so a flow reported by both sides is not
+//! counted twice.
+
+use crate::matrix::fetch_category_flows;
+use crate::{ComtradeFlow, FlowType};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeNetwork {
+    nodes: Vec<String>,
+    edges: BTreeMap<(usize, usize), f64>,
+}
+
+/// Weighted degree of one country.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Centrality {
+    pub country: String,
+    /// Total imports from every partner.
+    pub in_strength: f64,
+    /// Total exports to every partner.
+    pub out_strength: f64,
+    pub pagerank: f64,
+}
+
+/// How much of a country's trade is exposed to a shock in one partner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exposure {
+    /// Share of the country's total trade done directly with the partner.
+    pub direct: f64,
+    /// Share reached through third countries: for every other partner `k`,
+    /// the country's share with `k` times `k`'s share with the shocked
+    /// partner.
+    pub indirect: f64,
+    /// Trade value lost directly if trade with the partner falls by the
+    /// shock fraction.
+    pub direct_loss: f64,
+}
+
+impl TradeNetwork {
+    /// Fetches bilateral data for every ordered pair of `countries` and
+    /// builds the network for one commodity category in `year`.
+    pub fn fetch(
+        base_url: String,
+        client_key: String,
+        countries: &[&str],
+        category: &str,
+        year: i32,
+    ) -> Result<TradeNetwork, Box<dyn Error + Send + Sync>> {
+        let flows = fetch_category_flows(base_url, client_key, countries, category, &[year])?;
+        Ok(TradeNetwork::from_flows(&flows))
+    }
+
+    /// Builds the network from flows that should all belong to one category
+    /// and period; mixing nested categories would double count trade.
+    pub fn from_flows(flows: &[ComtradeFlow]) -> TradeNetwork {
+        let mut network = TradeNetwork::default();
+        let mut reported = BTreeMap::new();
+        let mut mirrored = BTreeMap::new();
+        for flow in flows {
+            let (Some(c1), Some(c2), Some(kind), Some(value)) = (
+                flow.country1.as_deref(),
+                flow.country2.as_deref(),
+                flow.flow(),
+                flow.value,
+            ) else {
+                continue;
+            };
+            let a = network.node(c1);
+            let b = network.node(c2);
+            match kind {
+                FlowType::Export => *reported.entry((a, b)).or_insert(0.0) += value,
+                FlowType::Import => *mirrored.entry((b, a)).or_insert(0.0) += value,
+            }
+        }
+        for (edge, value) in mirrored {
+            reported.entry(edge).or_insert(value);
+        }
+        network.edges = reported;
+        network
+    }
+
+    pub fn countries(&self) -> &[String] {
+        &self.nodes
+    }
+
+    /// Value shipped from `exporter` to `importer`.
+    pub fn weight(&self, exporter: &str, importer: &str) -> Option<f64> {
+        let a = self.position(exporter)?;
+        let b = self.position(importer)?;
+        self.edges.get(&(a, b)).copied()
+    }
+
+    pub fn exports(&self, country: &str) -> f64 {
+        self.position(country)
+            .map(|i| self.out_edges(i).map(|(_, w)| w).sum())
+            .unwrap_or(0.0)
+    }
+
+    pub fn imports(&self, country: &str) -> f64 {
+        self.position(country)
+            .map(|i| self.in_edges(i).map(|(_, w)| w).sum())
+            .unwrap_or(0.0)
+    }
+
+    /// Weighted degrees and PageRank (damping 0.85) for every country,
+    /// sorted by PageRank descending.
+    pub fn centrality(&self) -> Vec<Centrality> {
+        let ranks = self.pagerank(0.85, 100);
+        let mut out: Vec<Centrality> = (0..self.nodes.len())
+            .map(|i| Centrality {
+                country: self.nodes[i].clone(),
+                in_strength: self.in_edges(i).map(|(_, w)| w).sum(),
+                out_strength: self.out_edges(i).map(|(_, w)| w).sum(),
+                pagerank: ranks[i],
+            })
+            .collect();
+        out.sort_by(|a, b| b.pagerank.total_cmp(&a.pagerank));
+        out
+    }
+
+    /// Weighted PageRank following money towards importers' suppliers, so a
+    /// country ranks high when large importers buy from it.
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> Vec<f64> {
+        let n = self.nodes.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let imports: Vec<f64> = (0..n)
+            .map(|i| self.in_edges(i).map(|(_, w)| w).sum())
+            .collect();
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..iterations {
+            let mut next = vec![(1.0 - damping) / n as f64; n];
+            let mut dangling = 0.0;
+            for (i, &r) in rank.iter().enumerate() {
+                if imports[i] > 0.0 {
+                    for (supplier, w) in self.in_edges(i) {
+                        next[supplier] += damping * r * w / imports[i];
+                    }
+                } else {
+                    dangling += r;
+                }
+            }
+            for v in next.iter_mut() {
+                *v += damping * dangling / n as f64;
+            }
+            rank = next;
+        }
+        rank
+    }
+
+    /// The `n` partners with the most two-way trade with `country`.
+    pub fn largest_partners(&self, country: &str, n: usize) -> Vec<(String, f64)> {
+        let mut partners: Vec<(String, f64)> = self.partner_totals(country).into_iter().collect();
+        partners.sort_by(|a, b| b.1.total_cmp(&a.1));
+        partners.truncate(n);
+        partners
+    }
+
+    /// Herfindahl-Hirschman index of `country`'s partners for one direction,
+    /// between `1/partners` (evenly spread) and 1 (a single partner).
+    pub fn herfindahl(&self, country: &str, flow: FlowType) -> Option<f64> {
+        let i = self.position(country)?;
+        let values: Vec<f64> = match flow {
+            FlowType::Export => self.out_edges(i).map(|(_, w)| w).collect(),
+            FlowType::Import => self.in_edges(i).map(|(_, w)| w).collect(),
+        };
+        let total: f64 = values.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        Some(values.iter().map(|v| (v / total).powi(2)).sum())
+    }
+
+    /// Exposure of `country` to a fall of `shock` (e.g. `0.2` for 20%) in its
+    /// trade with `partner`.
+    pub fn exposure(&self, country: &str, partner: &str, shock: f64) -> Option<Exposure> {
+        let partner = match self.position(partner) {
+            Some(j) => self.nodes[j].as_str(),
+            None => partner,
+        };
+        let totals = self.partner_totals(country);
+        let total: f64 = totals.values().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let with_partner = totals.get(partner).copied().unwrap_or(0.0);
+        let mut indirect = 0.0;
+        for (k, value) in &totals {
+            if k == partner {
+                continue;
+            }
+            let k_totals = self.partner_totals(k);
+            let k_total: f64 = k_totals.values().sum();
+            if k_total > 0.0 {
+                let k_partner = k_totals.get(partner).copied().unwrap_or(0.0);
+                indirect += (value / total) * (k_partner / k_total);
+            }
+        }
+        Some(Exposure {
+            direct: with_partner / total,
+            indirect,
+            direct_loss: with_partner * shock,
+        })
+    }
+
+    /// Exports plus imports with every partner of `country`.
+    fn partner_totals(&self, country: &str) -> BTreeMap<String, f64> {
+        let mut totals = BTreeMap::new();
+        if let Some(i) = self.position(country) {
+            for (j, w) in self.out_edges(i).chain(self.in_edges(i)) {
+                *totals.entry(self.nodes[j].clone()).or_insert(0.0) += w;
+            }
+        }
+        totals
+    }
+
+    fn out_edges(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.edges
+            .range((i, 0)..(i + 1, 0))
+            .map(|(&(_, b), &w)| (b, w))
+    }
+
+    fn in_edges(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.edges
+            .iter()
+            .filter(move |((_, b), _)| *b == i)
+            .map(|(&(a, _), &w)| (a, w))
+    }
+
+    fn node(&mut self, name: &str) -> usize {
+        match self.position(name) {
+            Some(i) => i,
+            None => {
+                self.nodes.push(name.to_string());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.eq_ignore_ascii_case(name))
+    }
+}
@@ -0,0 +1,195 @@
+//! Decoding and building of Comtrade historical symbols.
+//!
+//! A symbol such as `PRTESP24031` packs four parts together: the reporter's
+//! ISO3 code (`PRT`), the partner's ISO3 code (`ESP`), the HS commodity code
+//! (`2403`) and the flow digit (`1` for imports, `2` for exports), following
+//! the UN Comtrade flow codes.
+
+use crate::{ComtradeCategory, ComtradeCountry, FlowType};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ComtradeSymbol {
+    pub reporter: String,
+    pub partner: String,
+    pub commodity: String,
+    pub flow: FlowType,
+}
+
+impl ComtradeSymbol {
+    /// Builds a symbol from its parts, validating each of them.
+    pub fn new(
+        reporter: &str,
+        partner: &str,
+        commodity: &str,
+        flow: FlowType,
+    ) -> Result<ComtradeSymbol, String> {
+        let reporter = iso3(reporter)?;
+        let partner = iso3(partner)?;
+        if commodity.is_empty() || !commodity.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("invalid commodity code: {}", commodity));
+        }
+        Ok(ComtradeSymbol {
+            reporter,
+            partner,
+            commodity: commodity.to_string(),
+            flow,
+        })
+    }
+
+    /// Builds the symbol for a plain-language request such as "Portugal
+    /// exports of tobacco to Spain": countries are matched by name or ISO3
+    /// code, the commodity by code or by name in the category tree.
+    pub fn resolve(
+        countries: &[ComtradeCountry],
+        categories: &CategoryTree,
+        reporter: &str,
+        partner: &str,
+        commodity: &str,
+        flow: FlowType,
+    ) -> Result<ComtradeSymbol, String> {
+        let reporter = country_iso(countries, reporter)?;
+        let partner = country_iso(countries, partner)?;
+        let category = categories
+            .get(commodity)
+            .or_else(|| categories.find(commodity))
+            .ok_or_else(|| format!("unknown commodity: {}", commodity))?;
+        ComtradeSymbol::new(&reporter, &partner, &category.id, flow)
+    }
+
+    /// The HS chapter (first two digits) of the commodity code.
+    pub fn chapter(&self) -> &str {
+        &self.commodity[..self.commodity.len().min(2)]
+    }
+}
+
+impl FromStr for ComtradeSymbol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() < 8 || !s.is_ascii() {
+            return Err(format!("invalid comtrade symbol: {}", s));
+        }
+        let (reporter, rest) = s.split_at(3);
+        let (partner, rest) = rest.split_at(3);
+        let (commodity, flow) = rest.split_at(rest.len() - 1);
+        let flow = match flow {
+            "1" => FlowType::Import,
+            "2" => FlowType::Export,
+            other => return Err(format!("unknown flow digit: {}", other)),
+        };
+        ComtradeSymbol::new(reporter, partner, commodity, flow)
+    }
+}
+
+impl fmt::Display for ComtradeSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flow = match self.flow {
+            FlowType::Import => '1',
+            FlowType::Export => '2',
+        };
+        write!(
+            f,
+            "{}{}{}{}",
+            self.reporter, self.partner, self.commodity, flow
+        )
+    }
+}
+
+/// The `/comtrade/categories` listing indexed by code, with parent links.
+#[derive(Debug, Clone, Default)]
+pub struct CategoryTree {
+    categories: HashMap<String, ComtradeCategory>,
+    children: HashMap<String, Vec<String>>,
+}
+
+impl CategoryTree {
+    pub fn new(categories: Vec<ComtradeCategory>) -> CategoryTree {
+        let mut tree = CategoryTree::default();
+        for category in categories {
+            if let Some(parent) = category.parent_id.as_ref().filter(|p| !p.is_empty()) {
+                tree.children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(category.id.clone());
+            }
+            tree.categories.insert(category.id.clone(), category);
+        }
+        for children in tree.children.values_mut() {
+            children.sort();
+        }
+        tree
+    }
+
+    pub fn get(&self, code: &str) -> Option<&ComtradeCategory> {
+        self.categories.get(code)
+    }
+
+    /// The most general category whose name contains `name`
+    /// (case-insensitive); ties go to the lowest code.
+    pub fn find(&self, name: &str) -> Option<&ComtradeCategory> {
+        let needle = name.to_lowercase();
+        self.categories
+            .values()
+            .filter(|c| {
+                c.name.to_lowercase().contains(&needle)
+                    || c.pretty_name
+                        .as_deref()
+                        .is_some_and(|p| p.to_lowercase().contains(&needle))
+            })
+            .min_by(|a, b| a.id.len().cmp(&b.id.len()).then(a.id.cmp(&b.id)))
+    }
+
+    pub fn children(&self, code: &str) -> Vec<&ComtradeCategory> {
+        self.children
+            .get(code)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| self.categories.get(id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Categories from the root down to `code`.
+    pub fn path(&self, code: &str) -> Vec<&ComtradeCategory> {
+        let mut path = Vec::new();
+        let mut current = self.categories.get(code);
+        while let Some(category) = current {
+            if path.iter().any(|c: &&ComtradeCategory| c.id == category.id) {
+                break;
+            }
+            path.push(category);
+            current = category
+                .parent_id
+                .as_deref()
+                .and_then(|p| self.categories.get(p));
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn iso3(code: &str) -> Result<String, String> {
+    if code.len() == 3 && code.bytes().all(|b| b.is_ascii_alphabetic()) {
+        Ok(code.to_uppercase())
+    } else {
+        Err(format!("invalid ISO3 country code: {}", code))
+    }
+}
+
+fn country_iso(countries: &[ComtradeCountry], name: &str) -> Result<String, String> {
+    countries
+        .iter()
+        .find(|c| {
+            c.name.eq_ignore_ascii_case(name)
+                || c.iso
+                    .as_deref()
+                    .is_some_and(|i| i.eq_ignore_ascii_case(name))
+        })
+        .and_then(|c| c.iso.clone())
+        .ok_or_else(|| format!("unknown comtrade country: {}", name))
+}
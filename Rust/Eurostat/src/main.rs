@@ -59,7 +59,7 @@ fn get_countries_list_and_categories(base_url:String, client_key:String) -> Resu
 
 fn get_data_by_group(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
     let path:String = String::from("/eurostat");
-    let params:String = String::from("?category_goup=Poverty");
+    let params:String = String::from("?category_group=Poverty");
     let url = format!("{}{}{}&c={}", base_url, path, params, client_key);
     let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
     println!("-----------------------Get Eurostat data by Category Group----------------------");
@@ -110,7 +110,7 @@ fn get_data_by_country_category(base_url:String, client_key:String) -> Result<()
 fn get_data_by_country_group(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
     let path:String = String::from("/eurostat/country");
     let params:String = String::from("/Denmark");
-    let group:String = String::from("?category_goup=Poverty");
+    let group:String = String::from("?category_group=Poverty");
     let url = format!("{}{}{}{}&c={}", base_url, path, params, group, client_key);
     let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
     println!("-----------------------Get Eurostat data by Country and category group----------------------");
@@ -68,7 +68,7 @@ fn get_forecast_multi_indicator(base_url:String, client_key:String) -> Result<()
 }
 
 fn get_forecast_country_indicator(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/historical/country");
+    let path:String = String::from("/forecast/country");
     let params1:String = String::from("/sweden");
     let params2:String = String::from("/indicator/gdp");
     let url = format!("{}{}{}{}?c={}", base_url, path, params1, params2, client_key);
@@ -80,7 +80,7 @@ fn get_forecast_country_indicator(base_url:String, client_key:String) -> Result<
 }
 
 fn get_forecast_multi_country_indicator(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
-    let path:String = String::from("/historical/country");
+    let path:String = String::from("/forecast/country");
     let param1:String = String::from("/mexico,sweden");
     let param2:String = String::from("/indicator/gdp,population");
     let url = format!("{}{}{}{}?c={}", base_url, path, param1, param2, client_key);
@@ -44,7 +44,7 @@ fn get_market_financials_by_country(base_url:String, client_key:String) -> Resul
 
 fn get_market_financials_by_countries(base_url:String, client_key:String) -> Result<(), Box<dyn Error>> {
     let path:String = String::from("/financials/companies");  
-    let params:String = String::from("?country=spain,germany}");
+    let params:String = String::from("?country=spain,germany");
     let url = format!("{}{}{}&c={}&f=json", base_url, path, params, client_key);
     let resp = reqwest::blocking::get(url)?.json::<serde_json::Value>()?;
     println!("-----------------------Financials companies list filtered by more than one country----------------------");
@@ -0,0 +1,4 @@
+//! The request helpers live in the `comtrade` crate so both crates share one
+//! copy.
+
+pub(crate) use comtrade::http::{encode, get_json, opt_string_or_number, string_or_number};
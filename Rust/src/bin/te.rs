@@ -0,0 +1,578 @@
+//! Command line front end for the tradingeconomics library.
+//!
+//! The client key is read from `TE_CLIENT_KEY` (default `guest:guest`) and
+//! the API root from `TE_BASE_URL`.
+
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::error::Error;
+use std::process;
+use tradingeconomics::calendar::{get_calendar, timezone, CalendarQuery, Importance};
+use tradingeconomics::feed::{Feed, FeedFilter, FeedFormat};
+use tradingeconomics::forecast::{
+    snapshot_indicator_forecasts, snapshot_market_forecasts, snapshot_market_forecasts_symbol,
+    store_accuracy, ForecastKind,
+};
+use tradingeconomics::ics::write_ics;
+use tradingeconomics::indicators::get_historical_country_indicator;
+use tradingeconomics::markets::get_market_historical;
+use tradingeconomics::news::{
+    get_news, get_news_country, get_news_country_indicator, get_news_indicator, NewsItem,
+};
+use tradingeconomics::ratings::{
+    get_ratings_historical, rating_changes, transition_matrix, Direction, RatingAction, GRADES,
+};
+use tradingeconomics::screen::{Expr, Screener};
+use tradingeconomics::search::{DocKind, SearchIndex, SearchQuery};
+use tradingeconomics::storage::Store;
+use tradingeconomics::timeseries::parse_date;
+
+const USAGE: &str = "usage: te <command> [--flag value ...]
+
+commands:
+  forecast-snapshot  --db PATH [--country A,B [--indicator X,Y]]
+                     [--market-category CAT] [--market-symbol S1,S2]
+                     [--vintage YYYY-MM-DD]
+  forecast-accuracy  --db PATH [--country C] [--series S] [--fetch-actuals]
+  news-fetch         --db PATH [--country A,B] [--indicator X,Y] [--limit N]
+  search             --db PATH WORDS... [--kind news|calendar] [--country A,B]
+                     [--category X,Y] [--from YYYY-MM-DD] [--to YYYY-MM-DD]
+                     [--limit N] [--facets]
+  feed               --out PATH [--format rss|atom] [--country A,B]
+                     [--indicator X,Y] [--only news|calendar] [--limit N]
+                     [--title TEXT]
+  screen             [--country A,B] [--where EXPR] [--sort FIELD [--asc]]
+                     [--limit N] [--db PATH [--max-age HOURS]]
+  ratings            --country A,B [--horizon MONTHS [--step MONTHS]]
+  calendar           [--country A,B] [--indicator X,Y] [--id A,B]
+                     [--importance low|medium|high] [--from YYYY-MM-DD --to YYYY-MM-DD]
+                     [--tz ZONE] [--db PATH]
+  ics                --out PATH [--country A,B] [--indicator X,Y] [--id A,B]
+                     [--importance low|medium|high] [--from YYYY-MM-DD --to YYYY-MM-DD]
+                     [--name TEXT]";
+
+type CliResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+struct Args {
+    flags: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Parses `--name value` pairs; a flag followed by another flag, or last
+    /// on the line, is a switch. Anything else is positional.
+    fn parse(raw: &[String]) -> Result<Args, String> {
+        let mut flags = HashMap::new();
+        let mut positional = Vec::new();
+        let mut i = 0;
+        while i < raw.len() {
+            let Some(name) = raw[i].strip_prefix("--") else {
+                positional.push(raw[i].clone());
+                i += 1;
+                continue;
+            };
+            match raw.get(i + 1).filter(|v| !v.starts_with("--")) {
+                Some(value) => {
+                    flags.insert(name.to_string(), value.clone());
+                    i += 2;
+                }
+                None => {
+                    flags.insert(name.to_string(), String::new());
+                    i += 1;
+                }
+            }
+        }
+        Ok(Args { flags, positional })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    fn require(&self, name: &str) -> Result<&str, String> {
+        self.get(name)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("missing --{}", name))
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.flags.contains_key(name)
+    }
+
+    fn list(&self, name: &str) -> Vec<&str> {
+        self.get(name)
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn main() {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = raw.split_first() else {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    };
+    let args = match Args::parse(rest) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let base_url =
+        std::env::var("TE_BASE_URL").unwrap_or_else(|_| "https://api.tradingeconomics.com".into());
+    let client_key = std::env::var("TE_CLIENT_KEY").unwrap_or_else(|_| "guest:guest".into());
+    let result = match command.as_str() {
+        "forecast-snapshot" => forecast_snapshot(base_url, client_key, &args),
+        "forecast-accuracy" => forecast_accuracy(base_url, client_key, &args),
+        "news-fetch" => news_fetch(base_url, client_key, &args),
+        "search" => search(&args),
+        "feed" => feed(base_url, client_key, &args),
+        "screen" => screen(base_url, client_key, &args),
+        "ratings" => ratings(base_url, client_key, &args),
+        "calendar" => calendar(base_url, client_key, &args),
+        "ics" => ics(base_url, client_key, &args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command: {}\n{}", other, USAGE).into()),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn forecast_snapshot(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let mut store = Store::open(args.require("db")?)?;
+    let vintage = match args.get("vintage") {
+        Some(v) => parse_date(v).ok_or_else(|| format!("bad --vintage: {}", v))?,
+        None => Utc::now().date_naive(),
+    };
+    let countries = args.list("country");
+    let categories = args.list("market-category");
+    let symbols = args.list("market-symbol");
+    if countries.is_empty() && categories.is_empty() && symbols.is_empty() {
+        return Err(
+            "nothing to snapshot: give --country, --market-category or --market-symbol".into(),
+        );
+    }
+    let mut total = 0;
+    if !countries.is_empty() {
+        total += snapshot_indicator_forecasts(
+            base_url.clone(),
+            client_key.clone(),
+            &mut store,
+            &countries,
+            &args.list("indicator"),
+            vintage,
+        )?;
+    }
+    for category in categories {
+        total += snapshot_market_forecasts(
+            base_url.clone(),
+            client_key.clone(),
+            &mut store,
+            category,
+            vintage,
+        )?;
+    }
+    if !symbols.is_empty() {
+        total += snapshot_market_forecasts_symbol(
+            base_url.clone(),
+            client_key.clone(),
+            &mut store,
+            &symbols,
+            vintage,
+        )?;
+    }
+    println!("stored {} projections for vintage {}", total, vintage);
+    Ok(())
+}
+
+fn forecast_accuracy(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let mut store = Store::open(args.require("db")?)?;
+    let country = args.get("country").filter(|v| !v.is_empty());
+    let series = args.get("series").filter(|v| !v.is_empty());
+    if args.has("fetch-actuals") {
+        let mut seen = Vec::new();
+        for v in store.forecast_vintages(country, series)? {
+            let key = (v.kind, v.country.to_lowercase(), v.series.to_lowercase());
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            match v.kind {
+                ForecastKind::Indicator => {
+                    let rows = get_historical_country_indicator(
+                        base_url.clone(),
+                        client_key.clone(),
+                        &v.country,
+                        &v.series,
+                    )?;
+                    store.upsert_indicator_history("/historical/country", &rows)?;
+                }
+                ForecastKind::Market => {
+                    let bars =
+                        get_market_historical(base_url.clone(), client_key.clone(), &v.series)?;
+                    store.upsert_market_bars("/markets/historical", &bars)?;
+                }
+            }
+        }
+    }
+    let rows = store_accuracy(&store, country, series)?;
+    println!(
+        "{:<10} {:<16} {:<28} {:>7} {:>5} {:>12} {:>8} {:>8}",
+        "kind", "country", "series", "horizon", "n", "mae", "mape", "hit"
+    );
+    for r in rows {
+        println!(
+            "{:<10} {:<16} {:<28} {:>7} {:>5} {:>12.4} {:>8} {:>8}",
+            r.kind.as_str(),
+            r.country,
+            r.series,
+            r.horizon.label(),
+            r.count,
+            r.mae,
+            percent(r.mape),
+            percent(r.hit_rate),
+        );
+    }
+    Ok(())
+}
+
+fn news_fetch(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let mut store = Store::open(args.require("db")?)?;
+    let (source, items) = fetch_news(base_url, client_key, args)?;
+    let n = store.upsert_news(source, &items)?;
+    println!("stored {} news items", n);
+    Ok(())
+}
+
+/// Fetches the news listing selected by `--country`, `--indicator` and
+/// `--limit`, with the endpoint it came from.
+fn fetch_news(
+    base_url: String,
+    client_key: String,
+    args: &Args,
+) -> Result<(&'static str, Vec<NewsItem>), Box<dyn Error + Send + Sync>> {
+    let limit = match args.get("limit") {
+        Some(v) => Some(v.parse().map_err(|_| format!("bad --limit: {}", v))?),
+        None => None,
+    };
+    let countries = args.list("country");
+    let indicators = args.list("indicator");
+    Ok(match (countries.is_empty(), indicators.is_empty()) {
+        (true, true) => ("/news", get_news(base_url, client_key, limit, None)?),
+        (false, true) => (
+            "/news/country",
+            get_news_country(base_url, client_key, &countries, limit, None)?,
+        ),
+        (true, false) => (
+            "/news/indicator",
+            get_news_indicator(base_url, client_key, &indicators, limit, None)?,
+        ),
+        (false, false) => (
+            "/news/country",
+            get_news_country_indicator(base_url, client_key, &countries, &indicators, limit, None)?,
+        ),
+    })
+}
+
+fn feed(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let out = args.require("out")?;
+    let format: FeedFormat = args.get("format").unwrap_or("rss").parse()?;
+    let (news, calendar) = match args.get("only") {
+        None => (true, true),
+        Some("news") => (true, false),
+        Some("calendar") => (false, true),
+        Some(other) => return Err(format!("bad --only: {}", other).into()),
+    };
+    let filter = FeedFilter {
+        countries: args.list("country").iter().map(|s| s.to_string()).collect(),
+        indicators: args
+            .list("indicator")
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    };
+    let mut feed = Feed::new(args.get("title").unwrap_or("Trading Economics"));
+    let mut added = 0;
+    if news {
+        let (_, items) = fetch_news(base_url.clone(), client_key.clone(), args)?;
+        added += feed.add_news(&items, &filter);
+    }
+    if calendar {
+        let events = get_calendar(base_url, client_key)?;
+        added += feed.add_upcoming(&events, &filter, Utc::now());
+    }
+    feed.write(out, format)?;
+    println!("wrote {} entries to {} ({})", added, out, format);
+    Ok(())
+}
+
+fn search(args: &Args) -> CliResult {
+    let store = Store::open(args.require("db")?)?;
+    let index = SearchIndex::from_store(&store)?;
+    let mut query = SearchQuery::new(&args.positional.join(" "));
+    for kind in args.list("kind") {
+        query.kinds.push(match kind {
+            "news" => DocKind::News,
+            "calendar" => DocKind::Calendar,
+            other => return Err(format!("bad --kind: {}", other).into()),
+        });
+    }
+    query.countries = args.list("country").iter().map(|s| s.to_string()).collect();
+    query.categories = args
+        .list("category")
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    query.from = date_flag(args, "from")?;
+    query.to = date_flag(args, "to")?;
+    query.limit = Some(match args.get("limit") {
+        Some(v) => v.parse().map_err(|_| format!("bad --limit: {}", v))?,
+        None => 20,
+    });
+    let hits = index.search(&query);
+    for hit in &hits {
+        let doc = hit.document;
+        println!(
+            "{:>7.3}  {:<8} {}  {:<16} {}",
+            hit.score,
+            doc.kind.as_str(),
+            doc.date.map(|d| d.to_string()).unwrap_or_default(),
+            doc.country.as_deref().unwrap_or(""),
+            doc.title,
+        );
+    }
+    if args.has("facets") {
+        let facets = SearchIndex::facets(&hits);
+        let line = |name: &str, values: Vec<(String, usize)>| {
+            let parts: Vec<String> = values
+                .iter()
+                .map(|(k, n)| format!("{} ({})", k, n))
+                .collect();
+            println!("{}: {}", name, parts.join(", "));
+        };
+        println!();
+        line(
+            "kind",
+            facets
+                .kinds
+                .iter()
+                .map(|(k, n)| (k.as_str().to_string(), *n))
+                .collect(),
+        );
+        line("country", facets.countries);
+        line("category", facets.categories);
+        line("month", facets.months);
+    }
+    Ok(())
+}
+
+fn screen(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let mut screener = Screener::new(&base_url, &client_key);
+    for country in args.list("country") {
+        screener = screener.country(country);
+    }
+    let filter: Option<Expr> = match args.get("where") {
+        Some(text) => Some(text.parse()?),
+        None => None,
+    };
+    if let Some(filter) = &filter {
+        screener = screener.filter(filter.clone());
+    }
+    let sort = args.get("sort").filter(|v| !v.is_empty());
+    if let Some(field) = sort {
+        screener = screener.sort(field, !args.has("asc"));
+    }
+    if let Some(v) = args.get("limit") {
+        screener = screener.limit(v.parse().map_err(|_| format!("bad --limit: {}", v))?);
+    }
+    if let Some(db) = args.get("db") {
+        let hours: i64 = match args.get("max-age") {
+            Some(v) => v.parse().map_err(|_| format!("bad --max-age: {}", v))?,
+            None => 24,
+        };
+        screener = screener.cache(Store::open(db)?, chrono::Duration::hours(hours));
+    }
+    let rows = screener.run()?;
+    let mut columns: Vec<String> = sort.map(str::to_lowercase).into_iter().collect();
+    for field in filter.iter().flat_map(Expr::fields) {
+        if !columns.iter().any(|c| c == field) {
+            columns.push(field.to_string());
+        }
+    }
+    print!("{:<14} {:<32}", "symbol", "name");
+    for c in &columns {
+        print!(" {:>16}", c);
+    }
+    println!();
+    for row in &rows {
+        print!(
+            "{:<14} {:<32}",
+            row.company.symbol,
+            row.company.name.as_deref().unwrap_or("")
+        );
+        for c in &columns {
+            match row.fields.get(c) {
+                Some(v) => print!(" {:>16.4}", v),
+                None => print!(" {:>16}", "-"),
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn ratings(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let countries = args.list("country");
+    if countries.is_empty() {
+        return Err("missing --country".into());
+    }
+    let rows = get_ratings_historical(base_url, client_key, &countries)?;
+    let history: Vec<RatingAction> = rows
+        .iter()
+        .filter_map(RatingAction::from_historical)
+        .collect();
+    println!(
+        "{:<10} {:<16} {:<8} {:>10} {:>10} {:>6}",
+        "date", "country", "agency", "from", "to", "delta"
+    );
+    for c in rating_changes(&history) {
+        println!(
+            "{:<10} {:<16} {:<8} {:>10} {:>10} {:>+6}  {}",
+            c.date,
+            c.country,
+            c.agency.as_str(),
+            c.from,
+            c.to,
+            c.delta,
+            match c.direction {
+                Direction::Upgrade => "upgrade",
+                Direction::Downgrade => "downgrade",
+            }
+        );
+    }
+    if let Some(v) = args.get("horizon") {
+        let horizon: u32 = v.parse().map_err(|_| format!("bad --horizon: {}", v))?;
+        let step: u32 = match args.get("step") {
+            Some(v) => v.parse().map_err(|_| format!("bad --step: {}", v))?,
+            None => horizon,
+        };
+        let matrix = transition_matrix(&history, horizon, step, Utc::now().date_naive());
+        let probabilities = matrix.probabilities();
+        let totals = matrix.row_totals();
+        println!("\n{}-month transitions (row: from, column: to)", horizon);
+        print!("{:<7}", "");
+        for g in GRADES {
+            print!(" {:>6}", g);
+        }
+        println!(" {:>6}", "n");
+        for (i, row) in probabilities.iter().enumerate() {
+            print!("{:<7}", GRADES[i]);
+            for p in row {
+                print!(" {:>6}", percent(*p));
+            }
+            println!(" {:>6}", totals[i]);
+        }
+    }
+    Ok(())
+}
+
+/// Query from the shared calendar filters; `--from`/`--to` are applied by
+/// the caller.
+fn calendar_query(args: &Args) -> Result<CalendarQuery, String> {
+    let mut query = CalendarQuery::new();
+    for country in args.list("country") {
+        query = query.country(country);
+    }
+    for indicator in args.list("indicator") {
+        query = query.indicator(indicator);
+    }
+    for id in args.list("id") {
+        query = query.calendar_id(id);
+    }
+    if let Some(v) = args.get("importance") {
+        let importance = Importance::parse(v).ok_or_else(|| format!("bad --importance: {}", v))?;
+        query = query.min_importance(importance);
+    }
+    Ok(query)
+}
+
+fn calendar(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let mut query = calendar_query(args)?;
+    match (date_flag(args, "from")?, date_flag(args, "to")?) {
+        (Some(from), Some(to)) => query = query.between(from, to),
+        (None, None) => {}
+        _ => return Err("give both --from and --to".into()),
+    }
+    let tz = timezone(args.get("tz").unwrap_or("UTC"))?;
+    let events = query.fetch(base_url, client_key)?;
+    if let Some(db) = args.get("db") {
+        Store::open(db)?.upsert_calendar_events(&query.path(), &events)?;
+    }
+    println!(
+        "{:<22} {:<16} {:<36} {:>10} {:>10} {:>10} {:>10}",
+        "release", "country", "event", "actual", "consensus", "te", "previous"
+    );
+    for e in &events {
+        let when = e
+            .release_in(&tz)
+            .map(|t| t.format("%Y-%m-%d %H:%M %Z").to_string())
+            .unwrap_or_else(|| e.date.clone());
+        println!(
+            "{:<22} {:<16} {:<36} {:>10} {:>10} {:>10} {:>10}",
+            when,
+            e.country,
+            e.event,
+            e.actual.as_deref().unwrap_or("-"),
+            e.forecast.as_deref().unwrap_or("-"),
+            e.te_forecast.as_deref().unwrap_or("-"),
+            e.previous.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+/// Without dates, exports the next two weeks of releases.
+fn ics(base_url: String, client_key: String, args: &Args) -> CliResult {
+    let out = args.require("out")?;
+    let today = Utc::now().date_naive();
+    let (from, to) = match (date_flag(args, "from")?, date_flag(args, "to")?) {
+        (Some(from), Some(to)) => (from, to),
+        (None, None) => (today, today + Duration::days(14)),
+        _ => return Err("give both --from and --to".into()),
+    };
+    let query = calendar_query(args)?.between(from, to);
+    let events = query.fetch(base_url, client_key)?;
+    write_ics(
+        out,
+        &events,
+        args.get("name").unwrap_or("Trading Economics calendar"),
+    )?;
+    println!("wrote {} events to {}", events.len(), out);
+    Ok(())
+}
+
+fn date_flag(args: &Args, name: &str) -> Result<Option<NaiveDate>, String> {
+    match args.get(name) {
+        Some(v) => parse_date(v)
+            .map(Some)
+            .ok_or_else(|| format!("bad --{}: {}", name, v)),
+        None => Ok(None),
+    }
+}
+
+fn percent(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.1}%", v * 100.0))
+        .unwrap_or_else(|| "-".to_string())
+}
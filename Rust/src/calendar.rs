@@ -0,0 +1,286 @@
+//! Economic calendar from `/calendar/...`.
+//!
+//! Values arrive as display strings ("2.5%", "-1.2K", "$3.1B"); the
+//! `*_value` accessors read them as numbers with the magnitude suffix
+//! applied. Release times are UTC and can be moved to any time zone with
+//! `release_in`.
+//!
+//! `CalendarQuery` picks the narrowest endpoint for its filters and applies
+//! the rest to the returned events, so the same filters also work on events
+//! read back from the store through `CalendarQuery::matches`.
+
+use crate::api::{encode, get_json};
+use crate::timeseries::{parse_date, parse_datetime};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// One release returned by the `/calendar` endpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarEvent {
+    pub calendar_id: String,
+    pub date: String,
+    pub country: String,
+    pub category: String,
+    pub event: String,
+    pub reference: Option<String>,
+    /// Last day of the period the release covers.
+    #[serde(default)]
+    pub reference_date: Option<String>,
+    pub source: Option<String>,
+    pub actual: Option<String>,
+    pub previous: Option<String>,
+    pub forecast: Option<String>,
+    #[serde(rename = "TEForecast")]
+    pub te_forecast: Option<String>,
+    pub importance: Option<i32>,
+    pub last_update: Option<String>,
+    pub currency: Option<String>,
+    pub unit: Option<String>,
+    pub ticker: Option<String>,
+    pub symbol: Option<String>,
+    /// Site path of the indicator page, e.g. `/united-states/gdp-growth`.
+    #[serde(rename = "URL", default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Importance {
+    Low = 1,
+    Medium = 2,
+    High = 3,
+}
+
+impl Importance {
+    pub fn from_level(level: i32) -> Option<Importance> {
+        match level {
+            1 => Some(Importance::Low),
+            2 => Some(Importance::Medium),
+            3 => Some(Importance::High),
+            _ => None,
+        }
+    }
+
+    /// Accepts `1`–`3` or `low`, `medium`, `high`.
+    pub fn parse(s: &str) -> Option<Importance> {
+        match s.trim().to_lowercase().as_str() {
+            "1" | "low" => Some(Importance::Low),
+            "2" | "medium" => Some(Importance::Medium),
+            "3" | "high" => Some(Importance::High),
+            _ => None,
+        }
+    }
+}
+
+impl CalendarEvent {
+    /// Release time in UTC.
+    pub fn release(&self) -> Option<DateTime<Utc>> {
+        parse_datetime(&self.date)
+    }
+
+    /// Release time in `tz`.
+    pub fn release_in<T: TimeZone>(&self, tz: &T) -> Option<DateTime<T>> {
+        self.release().map(|t| t.with_timezone(tz))
+    }
+
+    pub fn reference_date(&self) -> Option<NaiveDate> {
+        self.reference_date.as_deref().and_then(parse_date)
+    }
+
+    pub fn importance(&self) -> Option<Importance> {
+        self.importance.and_then(Importance::from_level)
+    }
+
+    pub fn actual_value(&self) -> Option<f64> {
+        self.actual.as_deref().and_then(parse_value)
+    }
+
+    pub fn previous_value(&self) -> Option<f64> {
+        self.previous.as_deref().and_then(parse_value)
+    }
+
+    /// Consensus forecast.
+    pub fn forecast_value(&self) -> Option<f64> {
+        self.forecast.as_deref().and_then(parse_value)
+    }
+
+    pub fn te_forecast_value(&self) -> Option<f64> {
+        self.te_forecast.as_deref().and_then(parse_value)
+    }
+
+    /// `Country: Event (Reference)`.
+    pub fn title(&self) -> String {
+        match &self.reference {
+            Some(r) if !r.is_empty() => format!("{}: {} ({})", self.country, self.event, r),
+            _ => format!("{}: {}", self.country, self.event),
+        }
+    }
+
+    /// `Label: value` for the actual, consensus, TE forecast and previous
+    /// values that are published.
+    pub fn value_lines(&self) -> Vec<String> {
+        [
+            ("Actual", &self.actual),
+            ("Consensus", &self.forecast),
+            ("TE forecast", &self.te_forecast),
+            ("Previous", &self.previous),
+        ]
+        .into_iter()
+        .filter_map(|(label, v)| {
+            v.as_deref()
+                .filter(|v| !v.is_empty())
+                .map(|v| format!("{}: {}", label, v))
+        })
+        .collect()
+    }
+
+    /// Actual minus consensus, when both are known.
+    pub fn surprise(&self) -> Option<f64> {
+        Some(self.actual_value()? - self.forecast_value()?)
+    }
+}
+
+/// Reads a display value: currency signs, `%` and thousands separators are
+/// dropped and a trailing `K`, `M`, `B` or `T` scales the number.
+pub fn parse_value(s: &str) -> Option<f64> {
+    let cleaned: String = s
+        .trim()
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'K' | 'M' | 'B' | 'T'))
+        .collect();
+    let (number, scale) = match cleaned.chars().last()? {
+        'K' => (&cleaned[..cleaned.len() - 1], 1e3),
+        'M' => (&cleaned[..cleaned.len() - 1], 1e6),
+        'B' => (&cleaned[..cleaned.len() - 1], 1e9),
+        'T' => (&cleaned[..cleaned.len() - 1], 1e12),
+        _ => (cleaned.as_str(), 1.0),
+    };
+    number.parse::<f64>().ok().map(|v| v * scale)
+}
+
+/// Looks up an IANA time zone name such as `Europe/Berlin`.
+pub fn timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse()
+        .map_err(|_| format!("unknown time zone: {}", name.trim()))
+}
+
+pub fn get_calendar(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<CalendarEvent>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/calendar");
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Builds a calendar request. Unset filters match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalendarQuery {
+    countries: Vec<String>,
+    indicators: Vec<String>,
+    ids: Vec<String>,
+    min_importance: Option<Importance>,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+}
+
+impl CalendarQuery {
+    pub fn new() -> CalendarQuery {
+        CalendarQuery::default()
+    }
+
+    pub fn country(mut self, country: &str) -> CalendarQuery {
+        self.countries.push(country.to_string());
+        self
+    }
+
+    /// Matched against the event category, e.g. `inflation rate`.
+    pub fn indicator(mut self, indicator: &str) -> CalendarQuery {
+        self.indicators.push(indicator.to_string());
+        self
+    }
+
+    pub fn calendar_id(mut self, id: &str) -> CalendarQuery {
+        self.ids.push(id.to_string());
+        self
+    }
+
+    /// Keeps events of at least this importance.
+    pub fn min_importance(mut self, importance: Importance) -> CalendarQuery {
+        self.min_importance = Some(importance);
+        self
+    }
+
+    /// Both ends are inclusive and compared with the UTC release date.
+    pub fn between(mut self, start: NaiveDate, end: NaiveDate) -> CalendarQuery {
+        self.start = Some(start);
+        self.end = Some(end);
+        self
+    }
+
+    /// Endpoint path, without the client key. Calendar ids select events
+    /// directly; otherwise countries, indicators and dates go in the path.
+    pub fn path(&self) -> String {
+        if !self.ids.is_empty() {
+            return format!("/calendar/calendarid/{}", encode(&self.ids.join(",")));
+        }
+        let mut path = String::from("/calendar");
+        let dated = self.start.is_some() && self.end.is_some();
+        if !self.countries.is_empty() {
+            path = format!("{}/country/{}", path, encode(&self.countries.join(",")));
+        } else if dated && self.indicators.is_empty() {
+            path = format!("{}/country/all", path);
+        }
+        if !self.indicators.is_empty() {
+            path = format!("{}/indicator/{}", path, encode(&self.indicators.join(",")));
+        }
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            path = format!("{}/{}/{}", path, start, end);
+        }
+        path
+    }
+
+    /// Whether `event` passes every filter.
+    pub fn matches(&self, event: &CalendarEvent) -> bool {
+        let listed = |list: &[String], value: &str| {
+            list.is_empty() || list.iter().any(|l| l.eq_ignore_ascii_case(value))
+        };
+        if !listed(&self.ids, &event.calendar_id)
+            || !listed(&self.countries, &event.country)
+            || !listed(&self.indicators, &event.category)
+        {
+            return false;
+        }
+        if let Some(min) = self.min_importance {
+            if event.importance().is_none_or(|i| i < min) {
+                return false;
+            }
+        }
+        if self.start.is_some() || self.end.is_some() {
+            let Some(date) = event.release().map(|t| t.date_naive()) else {
+                return false;
+            };
+            if self.start.is_some_and(|s| date < s) || self.end.is_some_and(|e| date > e) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Fetches and filters, in release order.
+    pub fn fetch(
+        &self,
+        base_url: String,
+        client_key: String,
+    ) -> Result<Vec<CalendarEvent>, Box<dyn Error + Send + Sync>> {
+        let url = format!("{}{}?c={}&f=json", base_url, self.path(), client_key);
+        let events: Vec<CalendarEvent> = get_json(url)?;
+        let mut events: Vec<CalendarEvent> =
+            events.into_iter().filter(|e| self.matches(e)).collect();
+        events.sort_by_key(|e| e.release());
+        Ok(events)
+    }
+}
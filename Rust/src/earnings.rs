@@ -0,0 +1,413 @@
+//! Earnings and revenues calendar from `/earnings-revenues/...`.
+//!
+//! Every event carries the reported EPS and revenue next to their consensus
+//! forecasts, so the surprise and a beat/miss call can be derived per event
+//! and rolled up into beat rates for any grouping of companies.
+
+use crate::api::{encode, get_json};
+use crate::markets::{get_market_components, MarketComponent};
+use crate::timeseries::parse_date;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// One row of the earnings calendar.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EarningsEvent {
+    pub date: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub sector: Option<String>,
+    /// `earnings`, `ipo`, `dividends` or `splits`.
+    #[serde(default, rename = "Type")]
+    pub event_type: Option<String>,
+    /// Before or after the market session, as published (`BMO`, `AMC`).
+    #[serde(default)]
+    pub report_time: Option<String>,
+    /// Time of day of the release, when published.
+    #[serde(default)]
+    pub time: Option<String>,
+    /// Fiscal period label, e.g. `FY2023Q3`.
+    #[serde(default)]
+    pub fiscal_tag: Option<String>,
+    #[serde(default)]
+    pub fiscal_reference: Option<String>,
+    #[serde(default)]
+    pub calendar_reference: Option<String>,
+    #[serde(default, rename = "Actual", alias = "ActualEPS")]
+    pub actual_eps: Option<f64>,
+    #[serde(default, rename = "Forecast", alias = "ForecastEPS")]
+    pub forecast_eps: Option<f64>,
+    #[serde(default, rename = "Revenue", alias = "ActualRevenue")]
+    pub actual_revenue: Option<f64>,
+    #[serde(default, rename = "RevenueForecast", alias = "ForecastRevenue")]
+    pub forecast_revenue: Option<f64>,
+    #[serde(default, rename = "MarketCapUSD")]
+    pub market_cap_usd: Option<f64>,
+    #[serde(default)]
+    pub importance: Option<i32>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+}
+
+/// The kinds of calendar entry `/earnings?type=` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EarningsType {
+    Earnings,
+    Ipo,
+    Dividends,
+    Splits,
+}
+
+impl EarningsType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EarningsType::Earnings => "earnings",
+            EarningsType::Ipo => "ipo",
+            EarningsType::Dividends => "dividends",
+            EarningsType::Splits => "splits",
+        }
+    }
+}
+
+impl FromStr for EarningsType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "earnings" => Ok(EarningsType::Earnings),
+            "ipo" | "ipos" => Ok(EarningsType::Ipo),
+            "dividends" | "dividend" => Ok(EarningsType::Dividends),
+            "splits" | "split" => Ok(EarningsType::Splits),
+            other => Err(format!("unknown earnings type: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for EarningsType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Beat,
+    InLine,
+    Miss,
+}
+
+impl EarningsEvent {
+    pub fn date(&self) -> Option<NaiveDate> {
+        parse_date(&self.date)
+    }
+
+    /// Whether the event has been reported yet.
+    pub fn is_reported(&self) -> bool {
+        self.actual_eps.is_some() || self.actual_revenue.is_some()
+    }
+
+    /// EPS surprise as a fraction of the absolute consensus.
+    pub fn eps_surprise(&self) -> Option<f64> {
+        surprise(self.actual_eps?, self.forecast_eps?)
+    }
+
+    pub fn revenue_surprise(&self) -> Option<f64> {
+        surprise(self.actual_revenue?, self.forecast_revenue?)
+    }
+
+    /// Beat or miss on EPS; a surprise within `tolerance` (a fraction, e.g.
+    /// `0.01`) either way counts as in line.
+    pub fn eps_outcome(&self, tolerance: f64) -> Option<Outcome> {
+        Some(outcome(self.actual_eps?, self.forecast_eps?, tolerance))
+    }
+
+    pub fn revenue_outcome(&self, tolerance: f64) -> Option<Outcome> {
+        Some(outcome(
+            self.actual_revenue?,
+            self.forecast_revenue?,
+            tolerance,
+        ))
+    }
+}
+
+fn surprise(actual: f64, forecast: f64) -> Option<f64> {
+    if forecast == 0.0 {
+        None
+    } else {
+        Some((actual - forecast) / forecast.abs())
+    }
+}
+
+fn outcome(actual: f64, forecast: f64, tolerance: f64) -> Outcome {
+    let diff = match surprise(actual, forecast) {
+        Some(s) => s,
+        // A zero consensus has no relative surprise; compare the sign.
+        None => actual,
+    };
+    if diff > tolerance {
+        Outcome::Beat
+    } else if diff < -tolerance {
+        Outcome::Miss
+    } else {
+        Outcome::InLine
+    }
+}
+
+/// The default calendar, optionally restricted to `start_date..=end_date`
+/// (`yyyy-mm-dd`).
+pub fn get_earnings(
+    base_url: String,
+    client_key: String,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<EarningsEvent>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/earnings-revenues");
+    let url = dated_url(&base_url, &path, &client_key, start_date, end_date);
+    get_json(url)
+}
+
+pub fn get_earnings_symbol(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<EarningsEvent>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/earnings-revenues/symbol/{}", encode(&symbols.join(",")));
+    let url = dated_url(&base_url, &path, &client_key, start_date, end_date);
+    get_json(url)
+}
+
+pub fn get_earnings_country(
+    base_url: String,
+    client_key: String,
+    country: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<EarningsEvent>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/earnings-revenues/country/{}", encode(country));
+    let url = dated_url(&base_url, &path, &client_key, start_date, end_date);
+    get_json(url)
+}
+
+pub fn get_earnings_type(
+    base_url: String,
+    client_key: String,
+    kind: EarningsType,
+) -> Result<Vec<EarningsEvent>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/earnings");
+    let url = format!(
+        "{}{}?type={}&c={}&f=json",
+        base_url,
+        path,
+        kind.as_str(),
+        client_key
+    );
+    get_json(url)
+}
+
+/// The calendar for every component of an index such as `psi20:ind`.
+pub fn get_earnings_index(
+    base_url: String,
+    client_key: String,
+    index: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<EarningsEvent>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/earnings-revenues/index/{}", encode(index));
+    let url = dated_url(&base_url, &path, &client_key, start_date, end_date);
+    get_json(url)
+}
+
+fn dated_url(
+    base_url: &str,
+    path: &str,
+    client_key: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> String {
+    let mut url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    if let Some(d1) = start_date {
+        url = format!("{}&d1={}", url, d1);
+    }
+    if let Some(d2) = end_date {
+        url = format!("{}&d2={}", url, d2);
+    }
+    url
+}
+
+/// Client-side filter over fetched or stored events; unset fields match
+/// everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EarningsFilter {
+    pub event_type: Option<EarningsType>,
+    pub countries: Vec<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl EarningsFilter {
+    pub fn matches(&self, event: &EarningsEvent) -> bool {
+        if let Some(kind) = self.event_type {
+            let same = event
+                .event_type
+                .as_deref()
+                .and_then(|t| t.parse::<EarningsType>().ok())
+                .is_some_and(|t| t == kind);
+            if !same {
+                return false;
+            }
+        }
+        if !self.countries.is_empty() {
+            let Some(country) = event.country.as_deref() else {
+                return false;
+            };
+            if !self
+                .countries
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(country))
+            {
+                return false;
+            }
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let Some(date) = event.date() else {
+                return false;
+            };
+            if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn apply<'a>(&self, events: &'a [EarningsEvent]) -> Vec<&'a EarningsEvent> {
+        events.iter().filter(|e| self.matches(e)).collect()
+    }
+}
+
+/// EPS beat statistics for one group of companies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeatRate {
+    pub group: String,
+    /// Reported events with a consensus.
+    pub reported: usize,
+    pub beats: usize,
+    pub in_line: usize,
+    pub misses: usize,
+    pub beat_rate: f64,
+    pub mean_surprise: Option<f64>,
+}
+
+/// Beat rates on EPS per group. `group` names the group of an event, e.g.
+/// its sector, or its index looked up from index components; events it
+/// returns `None` for are skipped.
+pub fn beat_rates<F>(events: &[EarningsEvent], tolerance: f64, group: F) -> Vec<BeatRate>
+where
+    F: Fn(&EarningsEvent) -> Option<String>,
+{
+    let mut groups: BTreeMap<String, Vec<&EarningsEvent>> = BTreeMap::new();
+    for event in events {
+        if event.eps_outcome(tolerance).is_none() {
+            continue;
+        }
+        if let Some(name) = group(event) {
+            groups.entry(name).or_default().push(event);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(group, events)| {
+            let count = |o: Outcome| {
+                events
+                    .iter()
+                    .filter(|e| e.eps_outcome(tolerance) == Some(o))
+                    .count()
+            };
+            let surprises: Vec<f64> = events.iter().filter_map(|e| e.eps_surprise()).collect();
+            let beats = count(Outcome::Beat);
+            BeatRate {
+                reported: events.len(),
+                beats,
+                in_line: count(Outcome::InLine),
+                misses: count(Outcome::Miss),
+                beat_rate: beats as f64 / events.len() as f64,
+                mean_surprise: if surprises.is_empty() {
+                    None
+                } else {
+                    Some(surprises.iter().sum::<f64>() / surprises.len() as f64)
+                },
+                group,
+            }
+        })
+        .collect()
+}
+
+/// Beat rates per sector, for events that carry one.
+pub fn beat_rates_by_sector(events: &[EarningsEvent], tolerance: f64) -> Vec<BeatRate> {
+    beat_rates(events, tolerance, |e| e.sector.clone())
+}
+
+/// An index constituent with one of its calendar events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentReport {
+    pub component: MarketComponent,
+    pub event: EarningsEvent,
+}
+
+/// Constituents of `index` reporting between `from` and `to` inclusive,
+/// with their consensus, in report date order.
+pub fn index_reporting(
+    base_url: String,
+    client_key: String,
+    index: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ComponentReport>, Box<dyn Error + Send + Sync>> {
+    let components = get_market_components(base_url.clone(), client_key.clone(), &[index])?;
+    let start = from.format("%Y-%m-%d").to_string();
+    let end = to.format("%Y-%m-%d").to_string();
+    let events = get_earnings_index(base_url, client_key, index, Some(&start), Some(&end))?;
+    let mut out: Vec<ComponentReport> = events
+        .into_iter()
+        .filter(|e| e.date().is_some_and(|d| d >= from && d <= to))
+        .filter_map(|event| {
+            let component = components
+                .iter()
+                .find(|c| c.symbol.eq_ignore_ascii_case(&event.symbol))?;
+            Some(ComponentReport {
+                component: component.clone(),
+                event,
+            })
+        })
+        .collect();
+    out.sort_by_key(|r| r.event.date());
+    Ok(out)
+}
+
+/// Constituents of `index` reporting in the current Monday-to-Sunday week.
+pub fn index_reporting_this_week(
+    base_url: String,
+    client_key: String,
+    index: &str,
+) -> Result<Vec<ComponentReport>, Box<dyn Error + Send + Sync>> {
+    let (from, to) = week_of(Utc::now().date_naive());
+    index_reporting(base_url, client_key, index, from, to)
+}
+
+/// Monday and Sunday of the week containing `date`.
+pub fn week_of(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    (monday, monday + Duration::days(6))
+}
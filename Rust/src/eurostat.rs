@@ -0,0 +1,281 @@
+//! Eurostat series published through `/eurostat/...`.
+//!
+//! Series are filed under categories, and categories under category groups
+//! (`Poverty`, `Labour`, ...). `EurostatCatalog` loads that tree once so an
+//! `EurostatQuery` can be checked before it is sent: the API answers an
+//! unknown group, category or country with an empty list rather than an
+//! error.
+
+use crate::api::{encode, get_json, opt_string_or_number};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// One entry of `/eurostat/categories`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EurostatCategory {
+    #[serde(alias = "category")]
+    pub category: String,
+    #[serde(alias = "category_group", alias = "categoryGroup")]
+    pub category_group: String,
+}
+
+/// One entry of `/eurostat/countries`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EurostatCountry {
+    #[serde(rename = "Country", alias = "country")]
+    pub country: String,
+}
+
+/// One row of a `/eurostat` or `/eurostat/country/...` listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EurostatSeries {
+    #[serde(
+        default,
+        rename = "ID",
+        alias = "id",
+        deserialize_with = "opt_string_or_number"
+    )]
+    pub id: Option<String>,
+    #[serde(default, alias = "country")]
+    pub country: Option<String>,
+    #[serde(default, alias = "category")]
+    pub category: Option<String>,
+    #[serde(default, alias = "category_group", alias = "categoryGroup")]
+    pub category_group: Option<String>,
+    #[serde(default, alias = "frequency")]
+    pub frequency: Option<String>,
+    #[serde(default, alias = "unit")]
+    pub unit: Option<String>,
+    #[serde(default, alias = "Last", alias = "Value")]
+    pub latest_value: Option<f64>,
+    #[serde(default)]
+    pub latest_value_date: Option<String>,
+    #[serde(default, rename = "URL", alias = "url")]
+    pub url: Option<String>,
+    #[serde(default, alias = "lastUpdate")]
+    pub last_update: Option<String>,
+}
+
+/// One observation from `/eurostat/historical/{ids}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EurostatHistorical {
+    #[serde(
+        default,
+        rename = "ID",
+        alias = "id",
+        deserialize_with = "opt_string_or_number"
+    )]
+    pub id: Option<String>,
+    #[serde(default, alias = "symbol")]
+    pub symbol: Option<String>,
+    #[serde(default, alias = "country")]
+    pub country: Option<String>,
+    #[serde(default, alias = "category")]
+    pub category: Option<String>,
+    #[serde(alias = "DateTime", alias = "date")]
+    pub date: String,
+    #[serde(default, alias = "value")]
+    pub value: Option<f64>,
+    #[serde(default, alias = "frequency")]
+    pub frequency: Option<String>,
+}
+
+pub fn get_eurostat_categories(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<EurostatCategory>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/eurostat/categories");
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_eurostat_countries(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<EurostatCountry>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/eurostat/countries");
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Observations for one or more series IDs, optionally limited to
+/// `start_date..=end_date` (`yyyy-mm-dd`).
+pub fn get_eurostat_historical(
+    base_url: String,
+    client_key: String,
+    ids: &[&str],
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<EurostatHistorical>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/eurostat/historical/{}", encode(&ids.join(",")));
+    let mut url = format!("{}{}?c={}", base_url, path, client_key);
+    if let Some(d1) = start_date {
+        url = format!("{}&d1={}", url, d1);
+    }
+    if let Some(d2) = end_date {
+        url = format!("{}&d2={}", url, d2);
+    }
+    get_json(url)
+}
+
+/// The group → category tree and the country list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EurostatCatalog {
+    groups: BTreeMap<String, Vec<String>>,
+    countries: Vec<String>,
+}
+
+impl EurostatCatalog {
+    pub fn load(
+        base_url: String,
+        client_key: String,
+    ) -> Result<EurostatCatalog, Box<dyn Error + Send + Sync>> {
+        let categories = get_eurostat_categories(base_url.clone(), client_key.clone())?;
+        let countries = get_eurostat_countries(base_url, client_key)?;
+        Ok(EurostatCatalog::new(categories, countries))
+    }
+
+    pub fn new(
+        categories: Vec<EurostatCategory>,
+        countries: Vec<EurostatCountry>,
+    ) -> EurostatCatalog {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for c in categories {
+            let members = groups.entry(c.category_group).or_default();
+            if !members.contains(&c.category) {
+                members.push(c.category);
+            }
+        }
+        for members in groups.values_mut() {
+            members.sort();
+        }
+        let mut countries: Vec<String> = countries.into_iter().map(|c| c.country).collect();
+        countries.sort();
+        countries.dedup();
+        EurostatCatalog { groups, countries }
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Categories of `group`, or `None` for an unknown group.
+    pub fn categories(&self, group: &str) -> Option<&[String]> {
+        self.groups
+            .iter()
+            .find(|(g, _)| g.eq_ignore_ascii_case(group))
+            .map(|(_, members)| members.as_slice())
+    }
+
+    /// The group a category is filed under.
+    pub fn group_of(&self, category: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(_, members)| members.iter().any(|c| c.eq_ignore_ascii_case(category)))
+            .map(|(g, _)| g.as_str())
+    }
+
+    pub fn countries(&self) -> &[String] {
+        &self.countries
+    }
+
+    pub fn has_country(&self, country: &str) -> bool {
+        self.countries
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(country))
+    }
+}
+
+/// A snapshot query against `/eurostat` or `/eurostat/country/{country}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EurostatQuery {
+    country: Option<String>,
+    category: Option<String>,
+    category_group: Option<String>,
+}
+
+impl EurostatQuery {
+    pub fn new() -> EurostatQuery {
+        EurostatQuery::default()
+    }
+
+    pub fn country(mut self, country: &str) -> EurostatQuery {
+        self.country = Some(country.to_string());
+        self
+    }
+
+    pub fn category(mut self, category: &str) -> EurostatQuery {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    pub fn category_group(mut self, group: &str) -> EurostatQuery {
+        self.category_group = Some(group.to_string());
+        self
+    }
+
+    /// Checks that every part exists in the catalog and that the category
+    /// belongs to the group when both are given.
+    pub fn validate(&self, catalog: &EurostatCatalog) -> Result<(), String> {
+        if self.country.is_none() && self.category.is_none() && self.category_group.is_none() {
+            return Err("eurostat query needs a country, category or category group".to_string());
+        }
+        if let Some(country) = &self.country {
+            if !catalog.has_country(country) {
+                return Err(format!("unknown eurostat country: {}", country));
+            }
+        }
+        if let Some(group) = &self.category_group {
+            if catalog.categories(group).is_none() {
+                return Err(format!("unknown eurostat category group: {}", group));
+            }
+        }
+        if let Some(category) = &self.category {
+            let owner = catalog
+                .group_of(category)
+                .ok_or_else(|| format!("unknown eurostat category: {}", category))?;
+            if let Some(group) = &self.category_group {
+                if !owner.eq_ignore_ascii_case(group) {
+                    return Err(format!(
+                        "eurostat category {} belongs to group {}, not {}",
+                        category, owner, group
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Path and query string, without the client key. A category is more
+    /// specific than its group, so only the category is sent when both are
+    /// set.
+    pub fn path(&self) -> String {
+        let mut path = match &self.country {
+            Some(country) => format!("/eurostat/country/{}", encode(country)),
+            None => String::from("/eurostat"),
+        };
+        if let Some(category) = &self.category {
+            path = format!("{}?category={}", path, encode(category));
+        } else if let Some(group) = &self.category_group {
+            path = format!("{}?category_group={}", path, encode(group));
+        }
+        path
+    }
+
+    pub fn fetch(
+        &self,
+        base_url: String,
+        client_key: String,
+        catalog: &EurostatCatalog,
+    ) -> Result<Vec<EurostatSeries>, Box<dyn Error + Send + Sync>> {
+        self.validate(catalog)?;
+        let path = self.path();
+        let sep = if path.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}{}c={}", base_url, path, sep, client_key);
+        get_json(url)
+    }
+}
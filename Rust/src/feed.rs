@@ -0,0 +1,306 @@
+//! RSS 2.0 and Atom 1.0 feeds built from news items and calendar events.
+//!
+//! The API sends timestamps without an offset; they are UTC and are written
+//! as such (RFC 822 dates for RSS, RFC 3339 for Atom). Relative site paths
+//! are resolved against `https://tradingeconomics.com`.
+
+use crate::calendar::CalendarEvent;
+use crate::news::NewsItem;
+use crate::timeseries::parse_datetime;
+use chrono::{DateTime, Utc};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+const SITE: &str = "https://tradingeconomics.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FromStr for FeedFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FeedFormat, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "rss" => Ok(FeedFormat::Rss),
+            "atom" => Ok(FeedFormat::Atom),
+            other => Err(format!("unknown feed format: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for FeedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FeedFormat::Rss => "rss",
+            FeedFormat::Atom => "atom",
+        })
+    }
+}
+
+/// Countries and indicators an entry must belong to; an empty list matches
+/// everything. News items are matched on their category, calendar events on
+/// their category or event name, all case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedFilter {
+    pub countries: Vec<String>,
+    pub indicators: Vec<String>,
+}
+
+impl FeedFilter {
+    pub fn admits_news(&self, item: &NewsItem) -> bool {
+        matches(&self.countries, item.country.as_deref())
+            && matches(&self.indicators, item.category.as_deref())
+    }
+
+    pub fn admits_event(&self, event: &CalendarEvent) -> bool {
+        matches(&self.countries, Some(&event.country))
+            && (matches(&self.indicators, Some(&event.category))
+                || matches(&self.indicators, Some(&event.event)))
+    }
+}
+
+fn matches(list: &[String], value: Option<&str>) -> bool {
+    list.is_empty() || value.is_some_and(|v| list.iter().any(|l| l.eq_ignore_ascii_case(v)))
+}
+
+/// One `<item>` or `<entry>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEntry {
+    /// Stable identifier, used as the RSS guid and the Atom id.
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub summary: Option<String>,
+    pub published: DateTime<Utc>,
+    pub categories: Vec<String>,
+}
+
+impl FeedEntry {
+    /// `None` when the item date cannot be read.
+    pub fn from_news(item: &NewsItem) -> Option<FeedEntry> {
+        Some(FeedEntry {
+            id: format!("tag:tradingeconomics.com,2000:news:{}", item.id),
+            title: item.title.clone(),
+            link: item.url.as_deref().map(absolute),
+            summary: item.description.clone(),
+            published: parse_datetime(&item.date)?,
+            categories: [&item.country, &item.category]
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// The summary carries the actual, consensus, TE forecast and previous
+    /// values that are known. `None` when the release date cannot be read.
+    pub fn from_event(event: &CalendarEvent) -> Option<FeedEntry> {
+        let values = event.value_lines();
+        Some(FeedEntry {
+            id: format!(
+                "tag:tradingeconomics.com,2000:calendar:{}",
+                event.calendar_id
+            ),
+            title: event.title(),
+            link: event.url.as_deref().map(absolute),
+            summary: (!values.is_empty()).then(|| values.join("; ")),
+            published: parse_datetime(&event.date)?,
+            categories: vec![event.country.clone(), event.category.clone()],
+        })
+    }
+}
+
+/// A feed under construction. Entries are written newest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    pub title: String,
+    pub link: String,
+    pub description: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    pub fn new(title: &str) -> Feed {
+        Feed {
+            title: title.to_string(),
+            link: SITE.to_string(),
+            description: title.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn link(mut self, link: &str) -> Feed {
+        self.link = link.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Feed {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Adds the news items `filter` admits; returns how many were added.
+    pub fn add_news(&mut self, items: &[NewsItem], filter: &FeedFilter) -> usize {
+        let before = self.entries.len();
+        self.entries.extend(
+            items
+                .iter()
+                .filter(|i| filter.admits_news(i))
+                .filter_map(FeedEntry::from_news),
+        );
+        self.entries.len() - before
+    }
+
+    /// Adds the calendar events `filter` admits that are released at or
+    /// after `now`; returns how many were added.
+    pub fn add_upcoming(
+        &mut self,
+        events: &[CalendarEvent],
+        filter: &FeedFilter,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let before = self.entries.len();
+        self.entries.extend(
+            events
+                .iter()
+                .filter(|e| filter.admits_event(e))
+                .filter_map(FeedEntry::from_event)
+                .filter(|e| e.published >= now),
+        );
+        self.entries.len() - before
+    }
+
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Rss => self.to_rss(),
+            FeedFormat::Atom => self.to_atom(),
+        }
+    }
+
+    pub fn to_rss(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<rss version=\"2.0\">\n<channel>\n");
+        out.push_str(&element(2, "title", &self.title));
+        out.push_str(&element(2, "link", &self.link));
+        out.push_str(&element(2, "description", &self.description));
+        out.push_str(&element(2, "lastBuildDate", &self.updated().to_rfc2822()));
+        for entry in self.sorted() {
+            out.push_str("  <item>\n");
+            out.push_str(&element(4, "title", &entry.title));
+            if let Some(link) = &entry.link {
+                out.push_str(&element(4, "link", link));
+            }
+            if let Some(summary) = &entry.summary {
+                out.push_str(&element(4, "description", summary));
+            }
+            out.push_str(&format!(
+                "    <guid isPermaLink=\"false\">{}</guid>\n",
+                escape(&entry.id)
+            ));
+            out.push_str(&element(4, "pubDate", &entry.published.to_rfc2822()));
+            for category in &entry.categories {
+                out.push_str(&element(4, "category", category));
+            }
+            out.push_str("  </item>\n");
+        }
+        out.push_str("</channel>\n</rss>\n");
+        out
+    }
+
+    pub fn to_atom(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        out.push_str(&element(2, "id", &self.link));
+        out.push_str(&element(2, "title", &self.title));
+        out.push_str(&element(2, "subtitle", &self.description));
+        out.push_str(&format!("  <link href=\"{}\"/>\n", escape(&self.link)));
+        out.push_str(&element(2, "updated", &rfc3339(self.updated())));
+        out.push_str("  <author>\n");
+        out.push_str(&element(4, "name", "Trading Economics"));
+        out.push_str("  </author>\n");
+        for entry in self.sorted() {
+            out.push_str("  <entry>\n");
+            out.push_str(&element(4, "id", &entry.id));
+            out.push_str(&element(4, "title", &entry.title));
+            if let Some(link) = &entry.link {
+                out.push_str(&format!("    <link href=\"{}\"/>\n", escape(link)));
+            }
+            out.push_str(&element(4, "published", &rfc3339(entry.published)));
+            out.push_str(&element(4, "updated", &rfc3339(entry.published)));
+            if let Some(summary) = &entry.summary {
+                out.push_str(&element(4, "summary", summary));
+            }
+            for category in &entry.categories {
+                out.push_str(&format!("    <category term=\"{}\"/>\n", escape(category)));
+            }
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+        out
+    }
+
+    /// Renders the feed to `path`.
+    pub fn write<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: FeedFormat,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        fs::write(path, self.render(format))?;
+        Ok(())
+    }
+
+    fn sorted(&self) -> Vec<&FeedEntry> {
+        let mut entries: Vec<&FeedEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| b.published.cmp(&a.published).then(a.id.cmp(&b.id)));
+        entries
+    }
+
+    /// The newest entry date; an empty feed is as of now.
+    fn updated(&self) -> DateTime<Utc> {
+        self.entries
+            .iter()
+            .map(|e| e.published)
+            .max()
+            .unwrap_or_else(Utc::now)
+    }
+}
+
+pub(crate) fn absolute(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("{}/{}", SITE, url.trim_start_matches('/'))
+    }
+}
+
+fn rfc3339(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn element(indent: usize, name: &str, text: &str) -> String {
+    format!("{:indent$}<{name}>{}</{name}>\n", "", escape(text))
+}
+
+/// Escapes markup and drops characters XML 1.0 does not allow.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
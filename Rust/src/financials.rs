@@ -0,0 +1,452 @@
+//! Company fundamentals from `/financials/...`.
+//!
+//! Line items are addressed as `ticker:country:item` (`aapl:us:assets`).
+//! Ratios combine several items of one company, matched on the fiscal
+//! period end date each item is reported for, so a company whose year ends
+//! in September is never compared across mismatched quarters.
+
+use crate::api::{encode, get_json};
+use crate::timeseries::{parse_date, AlignedSeries, Frequency, TimeSeries};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// One entry of `/financials/companies`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Company {
+    pub symbol: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub sector: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+    #[serde(default, rename = "MarketCapUSD", alias = "MarketCapitalization")]
+    pub market_cap_usd: Option<f64>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+}
+
+/// One latest line item from `/financials/symbol/{symbols}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FinancialItem {
+    pub symbol: String,
+    /// `ticker:country:item` of the item's history.
+    #[serde(default)]
+    pub financial_symbol: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default, alias = "Last")]
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub frequency: Option<String>,
+    /// End of the fiscal period the value is reported for.
+    #[serde(default, alias = "DateTime")]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+}
+
+impl FinancialItem {
+    /// The item part of `financial_symbol`, or the category as a slug.
+    pub fn item(&self) -> Option<String> {
+        self.financial_symbol
+            .as_deref()
+            .and_then(|s| s.parse::<FinancialSymbol>().ok())
+            .map(|s| s.item)
+            .or_else(|| self.category.as_deref().map(slug))
+    }
+}
+
+/// One observation from `/financials/historical/{symbols}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FinancialHistorical {
+    pub symbol: String,
+    #[serde(alias = "DateTime")]
+    pub date: String,
+    #[serde(default, alias = "Last")]
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub frequency: Option<String>,
+    #[serde(default)]
+    pub last_update: Option<String>,
+}
+
+/// A line item history symbol, e.g. `aapl:us:assets`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FinancialSymbol {
+    pub ticker: String,
+    pub country: String,
+    /// Item slug: lowercase, words joined by `-`.
+    pub item: String,
+}
+
+impl FinancialSymbol {
+    /// `item` may be given as a category name ("Gross Profit").
+    pub fn new(ticker: &str, country: &str, item: &str) -> FinancialSymbol {
+        FinancialSymbol {
+            ticker: ticker.trim().to_lowercase(),
+            country: country.trim().to_lowercase(),
+            item: slug(item),
+        }
+    }
+
+    /// The `ticker:country` company symbol.
+    pub fn company(&self) -> String {
+        format!("{}:{}", self.ticker, self.country)
+    }
+}
+
+impl FromStr for FinancialSymbol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().splitn(3, ':').collect();
+        match parts[..] {
+            [ticker, country, item]
+                if !ticker.is_empty() && !country.is_empty() && !item.is_empty() =>
+            {
+                Ok(FinancialSymbol::new(ticker, country, item))
+            }
+            _ => Err(format!("invalid financial symbol: {}", s.trim())),
+        }
+    }
+}
+
+impl fmt::Display for FinancialSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.ticker, self.country, self.item)
+    }
+}
+
+pub fn get_financials_companies(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<Company>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/financials/companies");
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_financials_companies_country(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+) -> Result<Vec<Company>, Box<dyn Error + Send + Sync>> {
+    let path = format!(
+        "/financials/companies?country={}",
+        encode(&countries.join(","))
+    );
+    let url = format!("{}{}&c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Latest line items of the `ticker:country` companies in `symbols`.
+pub fn get_financials_symbol(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+) -> Result<Vec<FinancialItem>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/financials/symbol/{}", encode(&symbols.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Builds a `/financials/historical/...` request for one or more items,
+/// optionally limited to a date range.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FinancialHistoryQuery {
+    symbols: Vec<FinancialSymbol>,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+}
+
+impl FinancialHistoryQuery {
+    pub fn new() -> FinancialHistoryQuery {
+        FinancialHistoryQuery::default()
+    }
+
+    pub fn symbol(mut self, symbol: FinancialSymbol) -> FinancialHistoryQuery {
+        self.symbols.push(symbol);
+        self
+    }
+
+    /// Adds `items` of the `ticker:country` company.
+    pub fn items(mut self, company: &str, items: &[&str]) -> Result<FinancialHistoryQuery, String> {
+        let (ticker, country) = company
+            .split_once(':')
+            .ok_or_else(|| format!("invalid company symbol: {}", company))?;
+        for item in items {
+            self.symbols
+                .push(FinancialSymbol::new(ticker, country, item));
+        }
+        Ok(self)
+    }
+
+    pub fn start(mut self, start: NaiveDate) -> FinancialHistoryQuery {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn end(mut self, end: NaiveDate) -> FinancialHistoryQuery {
+        self.end = Some(end);
+        self
+    }
+
+    /// Path and date parameters, without the client key.
+    pub fn path(&self) -> Result<String, String> {
+        if self.symbols.is_empty() {
+            return Err("financial history query needs at least one symbol".to_string());
+        }
+        let symbols: Vec<String> = self.symbols.iter().map(|s| s.to_string()).collect();
+        let mut path = format!("/financials/historical/{}", encode(&symbols.join(",")));
+        let mut sep = '?';
+        if let Some(start) = self.start {
+            path = format!("{}{}d1={}", path, sep, start);
+            sep = '&';
+        }
+        if let Some(end) = self.end {
+            path = format!("{}{}d2={}", path, sep, end);
+        }
+        Ok(path)
+    }
+
+    pub fn fetch(
+        &self,
+        base_url: String,
+        client_key: String,
+    ) -> Result<Vec<FinancialHistorical>, Box<dyn Error + Send + Sync>> {
+        let path = self.path()?;
+        let sep = if path.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}{}c={}&f=json", base_url, path, sep, client_key);
+        get_json(url)
+    }
+}
+
+/// Splits historical rows into one series per symbol, keyed by the
+/// lowercased `ticker:country:item`. Rows without a value or a readable date
+/// are skipped; the frequency comes from the rows, quarterly if absent.
+pub fn history_series(rows: &[FinancialHistorical]) -> BTreeMap<String, TimeSeries> {
+    let mut groups: BTreeMap<String, Vec<&FinancialHistorical>> = BTreeMap::new();
+    for row in rows {
+        groups
+            .entry(row.symbol.to_lowercase())
+            .or_default()
+            .push(row);
+    }
+    groups
+        .into_iter()
+        .map(|(symbol, rows)| {
+            let frequency = rows
+                .iter()
+                .find_map(|r| r.frequency.as_deref().and_then(|f| f.parse().ok()))
+                .unwrap_or(Frequency::Quarterly);
+            let points = rows
+                .iter()
+                .filter_map(|r| Some((parse_date(&r.date)?, r.value?)))
+                .collect();
+            let series = TimeSeries::new(&symbol, frequency, points);
+            (symbol, series)
+        })
+        .collect()
+}
+
+/// Ratios derived from a company's line items.
+///
+/// Inputs are looked up by item slug; margins and returns are for the
+/// reporting period as published, so quarterly ROE is not annualised.
+/// Growth compares a period with the one ending about a year before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ratio {
+    GrossMargin,
+    OperatingMargin,
+    NetMargin,
+    DebtToEquity,
+    DebtToAssets,
+    Roe,
+    Roa,
+    RevenueGrowth,
+    EarningsGrowth,
+}
+
+impl Ratio {
+    pub const ALL: [Ratio; 9] = [
+        Ratio::GrossMargin,
+        Ratio::OperatingMargin,
+        Ratio::NetMargin,
+        Ratio::DebtToEquity,
+        Ratio::DebtToAssets,
+        Ratio::Roe,
+        Ratio::Roa,
+        Ratio::RevenueGrowth,
+        Ratio::EarningsGrowth,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ratio::GrossMargin => "gross_margin",
+            Ratio::OperatingMargin => "operating_margin",
+            Ratio::NetMargin => "net_margin",
+            Ratio::DebtToEquity => "debt_to_equity",
+            Ratio::DebtToAssets => "debt_to_assets",
+            Ratio::Roe => "roe",
+            Ratio::Roa => "roa",
+            Ratio::RevenueGrowth => "revenue_growth",
+            Ratio::EarningsGrowth => "earnings_growth",
+        }
+    }
+
+    /// Numerator and denominator item slugs; growth ratios have a single
+    /// input.
+    pub fn inputs(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            Ratio::GrossMargin => ("gross-profit", Some("revenue")),
+            Ratio::OperatingMargin => ("ebit", Some("revenue")),
+            Ratio::NetMargin => ("net-income", Some("revenue")),
+            Ratio::DebtToEquity => ("debt", Some("equity")),
+            Ratio::DebtToAssets => ("debt", Some("assets")),
+            Ratio::Roe => ("net-income", Some("equity")),
+            Ratio::Roa => ("net-income", Some("assets")),
+            Ratio::RevenueGrowth => ("revenue", None),
+            Ratio::EarningsGrowth => ("net-income", None),
+        }
+    }
+
+    /// Every item slug `ratios` need, without repeats.
+    pub fn items(ratios: &[Ratio]) -> Vec<&'static str> {
+        let mut items: Vec<&'static str> = ratios
+            .iter()
+            .flat_map(|r| {
+                let (a, b) = r.inputs();
+                std::iter::once(a).chain(b)
+            })
+            .collect();
+        items.sort();
+        items.dedup();
+        items
+    }
+}
+
+impl FromStr for Ratio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase().replace('-', "_");
+        Ratio::ALL
+            .into_iter()
+            .find(|r| r.as_str() == s)
+            .ok_or_else(|| format!("unknown ratio: {}", s))
+    }
+}
+
+/// Computes `ratios` from one company's items, keyed by item slug, over the
+/// union of the items' fiscal period ends. A ratio is `None` in a period
+/// where an input is missing or a denominator is zero.
+pub fn ratios(items: &BTreeMap<String, TimeSeries>, ratios: &[Ratio]) -> AlignedSeries {
+    let mut dates: Vec<NaiveDate> = items
+        .values()
+        .flat_map(|s| s.points().iter().map(|p| p.0))
+        .collect();
+    dates.sort();
+    dates.dedup();
+    let frequency = items
+        .values()
+        .map(|s| s.frequency)
+        .min()
+        .unwrap_or(Frequency::Quarterly);
+    let columns = ratios
+        .iter()
+        .map(|ratio| {
+            let (num, den) = ratio.inputs();
+            let num = items.get(num);
+            let column = dates
+                .iter()
+                .map(|&date| {
+                    let a = num?.get(date)?;
+                    let b = match den {
+                        Some(den) => items.get(den)?.get(date)?,
+                        None => year_earlier(num?, date)?,
+                    };
+                    if b == 0.0 {
+                        return None;
+                    }
+                    Some(match den {
+                        Some(_) => a / b,
+                        None => (a - b) / b.abs(),
+                    })
+                })
+                .collect();
+            (ratio.as_str().to_string(), column)
+        })
+        .collect();
+    AlignedSeries {
+        frequency,
+        dates,
+        columns,
+    }
+}
+
+/// Fetches the items `ratio_list` needs for the `ticker:country` company and
+/// computes them.
+pub fn get_financial_ratios(
+    base_url: String,
+    client_key: String,
+    company: &str,
+    ratio_list: &[Ratio],
+    start: Option<NaiveDate>,
+) -> Result<AlignedSeries, Box<dyn Error + Send + Sync>> {
+    let mut query = FinancialHistoryQuery::new().items(company, &Ratio::items(ratio_list))?;
+    if let Some(start) = start {
+        // Growth needs the year before the first period.
+        query = query.start(start - Duration::days(400));
+    }
+    let rows = query.fetch(base_url, client_key)?;
+    let by_item: BTreeMap<String, TimeSeries> = history_series(&rows)
+        .into_iter()
+        .filter_map(|(symbol, series)| {
+            let item = symbol.parse::<FinancialSymbol>().ok()?.item;
+            Some((item, series))
+        })
+        .collect();
+    let mut aligned = ratios(&by_item, ratio_list);
+    if let Some(start) = start {
+        let skip = aligned.dates.iter().take_while(|d| **d < start).count();
+        aligned.dates.drain(..skip);
+        for (_, column) in aligned.columns.iter_mut() {
+            column.drain(..skip);
+        }
+    }
+    Ok(aligned)
+}
+
+/// The value of the period ending closest to one year before `date`,
+/// within three weeks either side.
+fn year_earlier(series: &TimeSeries, date: NaiveDate) -> Option<f64> {
+    let target = date - Duration::days(365);
+    series
+        .points()
+        .iter()
+        .filter(|(d, _)| (*d - target).num_days().abs() <= 21)
+        .min_by_key(|(d, _)| (*d - target).num_days().abs())
+        .map(|p| p.1)
+}
+
+fn slug(item: &str) -> String {
+    item.trim()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
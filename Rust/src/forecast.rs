@@ -0,0 +1,550 @@
+//! Indicator forecasts from `/forecast/...`.
+//!
+//! Each row carries the latest actual and two sets of projections: the next
+//! four quarters (`q1`..`q4`, each with its own date) and the end of the
+//! current and following two years. The API only serves the current
+//! forecast, so `compare` takes forecasts kept from earlier fetches and
+//! lines them up against the realised history.
+//!
+//! To keep those earlier fetches, the `snapshot_*` functions write every
+//! projection into the local store under a vintage date, the day the
+//! forecast was observed. `store_accuracy` later scores each vintage against
+//! the actuals in the store, per series and horizon.
+
+use crate::api::{encode, get_json};
+use crate::markets::{get_market_forecasts, get_market_forecasts_symbol, MarketForecast};
+use crate::storage::Store;
+use crate::timeseries::{parse_date, Frequency, TimeSeries};
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::str::FromStr;
+
+/// One row of a `/forecast/...` listing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct IndicatorForecast {
+    pub country: String,
+    pub category: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub latest_value: Option<f64>,
+    #[serde(default)]
+    pub latest_value_date: Option<String>,
+    #[serde(default, rename = "q1")]
+    pub q1: Option<f64>,
+    #[serde(default, rename = "q1_date")]
+    pub q1_date: Option<String>,
+    #[serde(default, rename = "q2")]
+    pub q2: Option<f64>,
+    #[serde(default, rename = "q2_date")]
+    pub q2_date: Option<String>,
+    #[serde(default, rename = "q3")]
+    pub q3: Option<f64>,
+    #[serde(default, rename = "q3_date")]
+    pub q3_date: Option<String>,
+    #[serde(default, rename = "q4")]
+    pub q4: Option<f64>,
+    #[serde(default, rename = "q4_date")]
+    pub q4_date: Option<String>,
+    #[serde(default)]
+    pub year_end: Option<f64>,
+    #[serde(default)]
+    pub year_end2: Option<f64>,
+    #[serde(default)]
+    pub year_end3: Option<f64>,
+    #[serde(default)]
+    pub frequency: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub historical_data_symbol: Option<String>,
+    #[serde(default)]
+    pub forecast_last_update: Option<String>,
+}
+
+/// How far ahead a projection looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Horizon {
+    /// `q1`..`q4`.
+    Quarter(u8),
+    /// `YearEnd` is 1, `YearEnd2` 2 and `YearEnd3` 3.
+    YearEnd(u8),
+}
+
+impl Horizon {
+    /// Short label used in tables: `q1`..`q4`, `y1`..`y3`.
+    pub fn label(&self) -> String {
+        match self {
+            Horizon::Quarter(n) => format!("q{}", n),
+            Horizon::YearEnd(n) => format!("y{}", n),
+        }
+    }
+
+    pub fn parse(label: &str) -> Option<Horizon> {
+        let kind = label.chars().next()?;
+        let n: u8 = label[kind.len_utf8()..].parse().ok()?;
+        match kind {
+            'q' | 'Q' if (1..=4).contains(&n) => Some(Horizon::Quarter(n)),
+            'y' | 'Y' if (1..=3).contains(&n) => Some(Horizon::YearEnd(n)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    pub horizon: Horizon,
+    /// The date the projected value refers to.
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+impl IndicatorForecast {
+    /// Quarterly projections followed by year-end ones. Year-end dates count
+    /// from the year of the forecast's last update, or of the latest actual
+    /// when the update time is missing.
+    pub fn projections(&self) -> Vec<Projection> {
+        let mut out = Vec::new();
+        let quarters = [
+            (self.q1, &self.q1_date),
+            (self.q2, &self.q2_date),
+            (self.q3, &self.q3_date),
+            (self.q4, &self.q4_date),
+        ];
+        for (i, (value, date)) in quarters.into_iter().enumerate() {
+            if let (Some(value), Some(date)) = (value, date.as_deref().and_then(parse_date)) {
+                out.push(Projection {
+                    horizon: Horizon::Quarter(i as u8 + 1),
+                    date,
+                    value,
+                });
+            }
+        }
+        let base_year = self
+            .forecast_last_update
+            .as_deref()
+            .or(self.latest_value_date.as_deref())
+            .and_then(parse_date)
+            .map(|d| d.year());
+        if let Some(year) = base_year {
+            let ends = [self.year_end, self.year_end2, self.year_end3];
+            for (i, value) in ends.into_iter().enumerate() {
+                let date = NaiveDate::from_ymd_opt(year + i as i32, 12, 31);
+                if let (Some(value), Some(date)) = (value, date) {
+                    out.push(Projection {
+                        horizon: Horizon::YearEnd(i as u8 + 1),
+                        date,
+                        value,
+                    });
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Forecasts for every indicator of one or more countries.
+pub fn get_forecast_country(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+) -> Result<Vec<IndicatorForecast>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/forecast/country/{}", encode(&countries.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Forecasts of one or more indicators across every country.
+pub fn get_forecast_indicator(
+    base_url: String,
+    client_key: String,
+    indicators: &[&str],
+) -> Result<Vec<IndicatorForecast>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/forecast/indicator/{}", encode(&indicators.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_forecast_country_indicator(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+    indicators: &[&str],
+) -> Result<Vec<IndicatorForecast>, Box<dyn Error + Send + Sync>> {
+    let path = format!(
+        "/forecast/country/{}/indicator/{}",
+        encode(&countries.join(",")),
+        encode(&indicators.join(","))
+    );
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// A past projection next to the value that was eventually reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastOutcome {
+    pub horizon: Horizon,
+    pub date: NaiveDate,
+    pub forecast: f64,
+    pub actual: f64,
+    /// `actual - forecast`.
+    pub error: f64,
+    /// The error relative to the actual, `None` when the actual is zero.
+    pub pct_error: Option<f64>,
+}
+
+/// Matches every projection of `forecasts` to the observation of `actual`
+/// in the same period, at the series' own frequency. Projections whose
+/// period has not been reported yet are left out.
+pub fn compare(forecasts: &[IndicatorForecast], actual: &TimeSeries) -> Vec<ForecastOutcome> {
+    let mut out = Vec::new();
+    for projection in forecasts.iter().flat_map(IndicatorForecast::projections) {
+        if let Some(value) = realised(actual, projection.date) {
+            let error = value - projection.value;
+            out.push(ForecastOutcome {
+                horizon: projection.horizon,
+                date: projection.date,
+                forecast: projection.value,
+                actual: value,
+                error,
+                pct_error: if value != 0.0 {
+                    Some(error / value)
+                } else {
+                    None
+                },
+            });
+        }
+    }
+    out
+}
+
+/// Which dataset a stored forecast belongs to, and so where its actuals
+/// live in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ForecastKind {
+    /// `/forecast/...`, realised in `indicator_history`.
+    Indicator,
+    /// `/markets/forecasts/...`, realised in `market_bars`.
+    Market,
+}
+
+impl ForecastKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ForecastKind::Indicator => "indicator",
+            ForecastKind::Market => "market",
+        }
+    }
+}
+
+impl FromStr for ForecastKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "indicator" => Ok(ForecastKind::Indicator),
+            "market" => Ok(ForecastKind::Market),
+            other => Err(format!("unknown forecast kind: {}", other)),
+        }
+    }
+}
+
+/// One projection as it stood on its vintage date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VintageForecast {
+    pub kind: ForecastKind,
+    /// The indicator's country, or the market's country when known.
+    pub country: String,
+    /// The indicator category, or the market symbol.
+    pub series: String,
+    pub vintage: NaiveDate,
+    pub horizon: Horizon,
+    pub target_date: NaiveDate,
+    pub value: f64,
+    /// The latest actual when the forecast was made, the reference for the
+    /// direction of the projected move.
+    pub base_value: Option<f64>,
+}
+
+impl VintageForecast {
+    pub fn from_indicator(row: &IndicatorForecast, vintage: NaiveDate) -> Vec<VintageForecast> {
+        row.projections()
+            .into_iter()
+            .map(|p| VintageForecast {
+                kind: ForecastKind::Indicator,
+                country: row.country.clone(),
+                series: row.category.clone(),
+                vintage,
+                horizon: p.horizon,
+                target_date: p.date,
+                value: p.value,
+                base_value: row.latest_value,
+            })
+            .collect()
+    }
+
+    /// Market projections target the next four quarter ends; when a row
+    /// carries no forecast dates they are counted from its quote date, or
+    /// from the vintage.
+    pub fn from_market(row: &MarketForecast, vintage: NaiveDate) -> Vec<VintageForecast> {
+        let quoted = row.date.as_deref().and_then(parse_date).unwrap_or(vintage);
+        let quarter_end = Frequency::Quarterly.period_end(quoted);
+        let forecasts = [
+            (row.forecast1, &row.forecast1_date),
+            (row.forecast2, &row.forecast2_date),
+            (row.forecast3, &row.forecast3_date),
+            (row.forecast4, &row.forecast4_date),
+        ];
+        let mut out = Vec::new();
+        for (i, (value, date)) in forecasts.into_iter().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+            let target = date.as_deref().and_then(parse_date).or_else(|| {
+                let first = quarter_end.with_day(1)?;
+                let start = first.checked_add_months(Months::new(3 * (i as u32 + 1)))?;
+                Some(Frequency::Quarterly.period_end(start))
+            });
+            if let Some(target_date) = target {
+                out.push(VintageForecast {
+                    kind: ForecastKind::Market,
+                    country: row.country.clone().unwrap_or_default(),
+                    series: row.symbol.clone(),
+                    vintage,
+                    horizon: Horizon::Quarter(i as u8 + 1),
+                    target_date,
+                    value,
+                    base_value: row.last,
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Fetches indicator forecasts for `countries` (all indicators when
+/// `indicators` is empty) and stores them under `vintage`.
+pub fn snapshot_indicator_forecasts(
+    base_url: String,
+    client_key: String,
+    store: &mut Store,
+    countries: &[&str],
+    indicators: &[&str],
+    vintage: NaiveDate,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let rows = if indicators.is_empty() {
+        get_forecast_country(base_url, client_key, countries)?
+    } else {
+        get_forecast_country_indicator(base_url, client_key, countries, indicators)?
+    };
+    let vintages: Vec<VintageForecast> = rows
+        .iter()
+        .flat_map(|r| VintageForecast::from_indicator(r, vintage))
+        .collect();
+    store.upsert_forecast_vintages("/forecast/country", &vintages)
+}
+
+/// Fetches market forecasts for a category and stores them under `vintage`.
+pub fn snapshot_market_forecasts(
+    base_url: String,
+    client_key: String,
+    store: &mut Store,
+    category: &str,
+    vintage: NaiveDate,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let rows = get_market_forecasts(base_url, client_key, category)?;
+    let vintages: Vec<VintageForecast> = rows
+        .iter()
+        .flat_map(|r| VintageForecast::from_market(r, vintage))
+        .collect();
+    store.upsert_forecast_vintages("/markets/forecasts", &vintages)
+}
+
+pub fn snapshot_market_forecasts_symbol(
+    base_url: String,
+    client_key: String,
+    store: &mut Store,
+    symbols: &[&str],
+    vintage: NaiveDate,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let rows = get_market_forecasts_symbol(base_url, client_key, symbols)?;
+    let vintages: Vec<VintageForecast> = rows
+        .iter()
+        .flat_map(|r| VintageForecast::from_market(r, vintage))
+        .collect();
+    store.upsert_forecast_vintages("/markets/forecasts/symbol", &vintages)
+}
+
+/// Kind, country and series of a stored forecast.
+type SeriesKey = (ForecastKind, String, String);
+
+/// Error metrics for one series and horizon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accuracy {
+    pub kind: ForecastKind,
+    pub country: String,
+    pub series: String,
+    pub horizon: Horizon,
+    /// Forecasts with a realised actual.
+    pub count: usize,
+    pub mae: f64,
+    /// Mean absolute percentage error as a fraction; actuals of zero are
+    /// left out.
+    pub mape: Option<f64>,
+    /// Share of forecasts that got the sign of the move from the base value
+    /// right; forecasts without a base value, or calling no move, are left
+    /// out.
+    pub hit_rate: Option<f64>,
+}
+
+/// Scores `forecasts` against `actual`, grouped by series and horizon.
+/// Forecasts whose target has not been realised yet are left out.
+pub fn accuracy(forecasts: &[VintageForecast], actual: &TimeSeries) -> Vec<Accuracy> {
+    let mut groups: BTreeMap<(SeriesKey, Horizon), Vec<(&VintageForecast, f64)>> = BTreeMap::new();
+    for f in forecasts {
+        if let Some(value) = realised(actual, f.target_date) {
+            groups
+                .entry(((f.kind, f.country.clone(), f.series.clone()), f.horizon))
+                .or_default()
+                .push((f, value));
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(((kind, country, series), horizon), obs)| {
+            let count = obs.len();
+            let mae = obs.iter().map(|(f, a)| (a - f.value).abs()).sum::<f64>() / count as f64;
+            let pct: Vec<f64> = obs
+                .iter()
+                .filter(|(_, a)| *a != 0.0)
+                .map(|(f, a)| ((a - f.value) / a).abs())
+                .collect();
+            let hits: Vec<f64> = obs
+                .iter()
+                .filter_map(|&(f, a)| {
+                    let base = f.base_value?;
+                    if f.value == base {
+                        return None;
+                    }
+                    let hit = a != base && (f.value - base).signum() == (a - base).signum();
+                    Some(if hit { 1.0 } else { 0.0 })
+                })
+                .collect();
+            Accuracy {
+                kind,
+                country,
+                series,
+                horizon,
+                count,
+                mae,
+                mape: mean(&pct),
+                hit_rate: mean(&hits),
+            }
+        })
+        .collect()
+}
+
+/// Scores every stored forecast vintage against the actuals already in the
+/// store, optionally limited to one country or series.
+pub fn store_accuracy(
+    store: &Store,
+    country: Option<&str>,
+    series: Option<&str>,
+) -> Result<Vec<Accuracy>, Box<dyn Error + Send + Sync>> {
+    let vintages = store.forecast_vintages(country, series)?;
+    let mut by_series: BTreeMap<SeriesKey, Vec<VintageForecast>> = BTreeMap::new();
+    for v in vintages {
+        by_series
+            .entry((v.kind, v.country.to_lowercase(), v.series.to_lowercase()))
+            .or_default()
+            .push(v);
+    }
+    let mut out = Vec::new();
+    for ((kind, _, _), forecasts) in by_series {
+        let first = &forecasts[0];
+        let actual = match kind {
+            ForecastKind::Indicator => {
+                let rows = store.indicator_history(&first.country, &first.series)?;
+                if rows.is_empty() {
+                    continue;
+                }
+                TimeSeries::from_historical(&rows)?
+            }
+            ForecastKind::Market => {
+                let bars = store.market_bars(&first.series)?;
+                if bars.is_empty() {
+                    continue;
+                }
+                TimeSeries::from_market_bars(&bars)?
+            }
+        };
+        out.extend(accuracy(&forecasts, &actual));
+    }
+    Ok(out)
+}
+
+/// The accuracy table as a DataFrame, one row per series and horizon.
+#[cfg(feature = "polars")]
+pub fn accuracy_frame(
+    rows: &[Accuracy],
+) -> polars::prelude::PolarsResult<polars::prelude::DataFrame> {
+    use polars::prelude::{DataFrame, NamedFrom, Series};
+    DataFrame::new(vec![
+        Series::new(
+            "kind",
+            rows.iter().map(|r| r.kind.as_str()).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "country",
+            rows.iter().map(|r| r.country.as_str()).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "series",
+            rows.iter().map(|r| r.series.as_str()).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "horizon",
+            rows.iter().map(|r| r.horizon.label()).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "count",
+            rows.iter().map(|r| r.count as u64).collect::<Vec<_>>(),
+        ),
+        Series::new("mae", rows.iter().map(|r| r.mae).collect::<Vec<_>>()),
+        Series::new("mape", rows.iter().map(|r| r.mape).collect::<Vec<_>>()),
+        Series::new(
+            "hit_rate",
+            rows.iter().map(|r| r.hit_rate).collect::<Vec<_>>(),
+        ),
+    ])
+}
+
+/// The observation of `actual` for the period containing `date`, at the
+/// series' own frequency. Daily series take the last close on or before
+/// `date`, once the series reaches it.
+fn realised(actual: &TimeSeries, date: NaiveDate) -> Option<f64> {
+    if actual.frequency == Frequency::Daily {
+        if actual.last()?.0 < date {
+            return None;
+        }
+        return actual.asof(date).map(|p| p.1);
+    }
+    let period = actual.frequency.period_end(date);
+    actual
+        .points()
+        .iter()
+        .rev()
+        .find(|(d, _)| actual.frequency.period_end(*d) == period)
+        .map(|p| p.1)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
@@ -0,0 +1,573 @@
+//! Federal Reserve Economic Data (FRED) browsing.
+//!
+//! FRED series are organised by US geography: the country, its states, and
+//! the counties of each state. `FredBrowser` walks that hierarchy lazily,
+//! fetching a node's children and its snapshot pages only when the iterator
+//! reaches them.
+
+use crate::api::{encode, get_json, opt_string_or_number};
+use crate::timeseries::parse_date;
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+pub const FRED_COUNTRY: &str = "united states";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FredState {
+    #[serde(alias = "Name", alias = "State", alias = "state")]
+    pub name: String,
+    #[serde(default, alias = "URL", alias = "Url")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FredCounty {
+    #[serde(alias = "County", alias = "county", alias = "Name")]
+    pub name: String,
+    /// Five-digit state and county code, e.g. `05007`.
+    #[serde(
+        default,
+        alias = "CountyFIPS",
+        alias = "countyFIPS",
+        alias = "FIPS",
+        alias = "fips",
+        deserialize_with = "opt_fips"
+    )]
+    pub fips: Option<String>,
+}
+
+/// One row of a `/fred/snapshot/...` listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FredSeries {
+    #[serde(alias = "symbol")]
+    pub symbol: String,
+    #[serde(default, alias = "Title", alias = "title")]
+    pub name: Option<String>,
+    #[serde(default, alias = "country")]
+    pub country: Option<String>,
+    #[serde(default, alias = "category")]
+    pub category: Option<String>,
+    #[serde(default, alias = "frequency")]
+    pub frequency: Option<String>,
+    #[serde(default, alias = "Last", alias = "Value")]
+    pub latest_value: Option<f64>,
+    #[serde(default)]
+    pub latest_value_date: Option<String>,
+    #[serde(default, rename = "URL", alias = "url")]
+    pub url: Option<String>,
+    #[serde(default, alias = "lastUpdate")]
+    pub last_update: Option<String>,
+}
+
+/// One observation from `/fred/historical/{symbols}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FredHistorical {
+    #[serde(alias = "symbol")]
+    pub symbol: String,
+    #[serde(alias = "date")]
+    pub date: String,
+    #[serde(alias = "value")]
+    pub value: Option<f64>,
+}
+
+/// A level of the FRED geography.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FredNode {
+    Country(String),
+    State(String),
+    County(String),
+}
+
+impl FredNode {
+    pub fn name(&self) -> &str {
+        match self {
+            FredNode::Country(n) | FredNode::State(n) | FredNode::County(n) => n,
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        match self {
+            FredNode::Country(_) => "country",
+            FredNode::State(_) => "state",
+            FredNode::County(_) => "county",
+        }
+    }
+}
+
+/// FIPS codes arrive as numbers or strings; numbers lose the leading zero
+/// of states 01–09, so digits are padded back to five.
+fn opt_fips<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(opt_string_or_number(d)?.map(|f| {
+        let f = f.trim();
+        if !f.is_empty() && f.len() < 5 && f.bytes().all(|b| b.is_ascii_digit()) {
+            format!("{:0>5}", f)
+        } else {
+            f.to_string()
+        }
+    }))
+}
+
+pub fn get_fred_states(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<FredState>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/fred/states");
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_fred_counties(
+    base_url: String,
+    client_key: String,
+    state: &str,
+) -> Result<Vec<FredCounty>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/fred/counties/{}", encode(state));
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Series available at `node`. Pages start at 1; `None` asks for the first
+/// page.
+pub fn get_fred_snapshot(
+    base_url: String,
+    client_key: String,
+    node: &FredNode,
+    page: Option<u32>,
+) -> Result<Vec<FredSeries>, Box<dyn Error + Send + Sync>> {
+    get_snapshot_page(base_url, client_key, &snapshot_path(node), page)
+}
+
+/// County-level series of every county in `state`, from
+/// `/fred/snapshot/county/{state}`.
+pub fn get_fred_state_county_snapshot(
+    base_url: String,
+    client_key: String,
+    state: &str,
+    page: Option<u32>,
+) -> Result<Vec<FredSeries>, Box<dyn Error + Send + Sync>> {
+    get_snapshot_page(base_url, client_key, &state_county_path(state), page)
+}
+
+fn snapshot_path(node: &FredNode) -> String {
+    format!("/fred/snapshot/{}/{}", node.level(), encode(node.name()))
+}
+
+fn state_county_path(state: &str) -> String {
+    format!("/fred/snapshot/county/{}", encode(state))
+}
+
+fn get_snapshot_page(
+    base_url: String,
+    client_key: String,
+    path: &str,
+    page: Option<u32>,
+) -> Result<Vec<FredSeries>, Box<dyn Error + Send + Sync>> {
+    let mut path = path.to_string();
+    if let Some(page) = page {
+        path = format!("{}/{}", path, page);
+    }
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_fred_snapshot_symbol(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+) -> Result<Vec<FredSeries>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/fred/snapshot/symbol/{}", encode(&symbols.join(",")));
+    let url = format!("{}{}?c={}", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Snapshot of the series behind a site path such as
+/// `/united-states/income-inequality-in-aleutians-east-borough-ak-fed-data.html`.
+pub fn get_fred_snapshot_url(
+    base_url: String,
+    client_key: String,
+    site_url: &str,
+) -> Result<Vec<FredSeries>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/fred/snapshot/url");
+    let url = format!(
+        "{}{}?url={}&c={}",
+        base_url,
+        path,
+        encode(site_url),
+        client_key
+    );
+    get_json(url)
+}
+
+pub fn get_fred_historical(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<FredHistorical>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/fred/historical/{}", encode(&symbols.join(",")));
+    let mut url = format!("{}{}?c={}", base_url, path, client_key);
+    if let Some(d1) = start_date {
+        url = format!("{}&d1={}", url, d1);
+    }
+    if let Some(d2) = end_date {
+        url = format!("{}&d2={}", url, d2);
+    }
+    get_json(url)
+}
+
+pub struct FredBrowser {
+    base_url: String,
+    client_key: String,
+}
+
+impl FredBrowser {
+    pub fn new(base_url: &str, client_key: &str) -> FredBrowser {
+        FredBrowser {
+            base_url: base_url.to_string(),
+            client_key: client_key.to_string(),
+        }
+    }
+
+    pub fn root(&self) -> FredNode {
+        FredNode::Country(FRED_COUNTRY.to_string())
+    }
+
+    /// States under the country, counties under a state; counties are
+    /// leaves.
+    pub fn children(&self, node: &FredNode) -> Result<Vec<FredNode>, Box<dyn Error + Send + Sync>> {
+        match node {
+            FredNode::Country(_) => Ok(get_fred_states(
+                self.base_url.clone(),
+                self.client_key.clone(),
+            )?
+            .into_iter()
+            .map(|s| FredNode::State(s.name))
+            .collect()),
+            FredNode::State(state) => {
+                Ok(
+                    get_fred_counties(self.base_url.clone(), self.client_key.clone(), state)?
+                        .into_iter()
+                        .map(|c| FredNode::County(c.name))
+                        .collect(),
+                )
+            }
+            FredNode::County(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Lazily pages through the series available at `node`.
+    pub fn series(&self, node: &FredNode) -> SeriesPages<'_> {
+        self.pages(snapshot_path(node))
+    }
+
+    /// Lazily pages through the county-level series of every county in
+    /// `state`.
+    pub fn state_county_series(&self, state: &str) -> SeriesPages<'_> {
+        self.pages(state_county_path(state))
+    }
+
+    fn pages(&self, path: String) -> SeriesPages<'_> {
+        SeriesPages {
+            browser: self,
+            path,
+            page: 1,
+            buffer: Vec::new().into_iter(),
+            last_first: None,
+            done: false,
+        }
+    }
+
+    /// Depth-first walk from `root`, yielding every series together with the
+    /// node it was found at.
+    pub fn walk(&self, root: FredNode) -> Walk<'_> {
+        Walk {
+            browser: self,
+            pending: vec![root],
+            current: None,
+        }
+    }
+
+    /// Every county-level series in `state` whose category contains
+    /// `category` (case-insensitive), e.g. `"income inequality"`.
+    pub fn county_series(
+        &self,
+        state: &str,
+        category: &str,
+    ) -> Result<Vec<(FredNode, FredSeries)>, Box<dyn Error + Send + Sync>> {
+        let needle = category.to_lowercase();
+        let mut found = Vec::new();
+        for county in self.children(&FredNode::State(state.to_string()))? {
+            for series in self.series(&county) {
+                let series = series?;
+                if matches_category(&series, &needle) {
+                    found.push((county.clone(), series));
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// One FRED measure across every county of `state` as a wide date×county
+    /// panel. Matching symbols are discovered through
+    /// `/fred/snapshot/county/{state}` (`measure` is a symbol prefix such as
+    /// `RACEDISPARITY`, or a category fragment such as `income inequality`)
+    /// and their histories are downloaded in chunks.
+    pub fn county_panel(
+        &self,
+        state: &str,
+        measure: &str,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+    ) -> Result<CountyPanel, Box<dyn Error + Send + Sync>> {
+        let needle = measure.to_lowercase();
+        let mut symbols = Vec::new();
+        for series in self.state_county_series(state) {
+            let series = series?;
+            if series.symbol.to_lowercase().starts_with(&needle)
+                || matches_category(&series, &needle)
+            {
+                symbols.push(series.symbol);
+            }
+        }
+        symbols.sort();
+        symbols.dedup();
+
+        let names: HashMap<String, String> =
+            get_fred_counties(self.base_url.clone(), self.client_key.clone(), state)?
+                .into_iter()
+                .filter_map(|c| Some((c.fips?, c.name)))
+                .collect();
+
+        let mut rows = Vec::new();
+        for chunk in symbols.chunks(HISTORICAL_CHUNK) {
+            let chunk: Vec<&str> = chunk.iter().map(|s| s.as_str()).collect();
+            rows.extend(get_fred_historical(
+                self.base_url.clone(),
+                self.client_key.clone(),
+                &chunk,
+                start_date,
+                end_date,
+            )?);
+        }
+        Ok(CountyPanel::from_historical(
+            state, measure, &symbols, &names, &rows,
+        ))
+    }
+}
+
+/// Symbols per `/fred/historical` request when assembling panels.
+const HISTORICAL_CHUNK: usize = 20;
+
+/// Upper bound on the snapshot pages `SeriesPages` walks for one node.
+const MAX_PAGES: u32 = 200;
+
+/// The county FIPS code carried at the end of county-level FRED symbols:
+/// `RACEDISPARITY005007` ends in `005007`, i.e. state `05`, county `007`.
+pub fn county_fips(symbol: &str) -> Option<String> {
+    let digits = symbol.len()
+        - symbol
+            .bytes()
+            .rev()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+    let tail = &symbol[digits..];
+    if tail.len() < 5 {
+        return None;
+    }
+    Some(tail[tail.len() - 5..].to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelCounty {
+    pub symbol: String,
+    pub fips: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Wide panel: `values[row][col]` is the value of `counties[col]` at
+/// `dates[row]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountyPanel {
+    pub state: String,
+    pub measure: String,
+    pub counties: Vec<PanelCounty>,
+    pub dates: Vec<NaiveDate>,
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+impl CountyPanel {
+    /// Assembles the panel from downloaded rows; `names` maps FIPS codes to
+    /// county names.
+    pub fn from_historical(
+        state: &str,
+        measure: &str,
+        symbols: &[String],
+        names: &HashMap<String, String>,
+        rows: &[FredHistorical],
+    ) -> CountyPanel {
+        let counties: Vec<PanelCounty> = symbols
+            .iter()
+            .map(|symbol| {
+                let fips = county_fips(symbol);
+                let name = fips.as_ref().and_then(|f| names.get(f).cloned());
+                PanelCounty {
+                    symbol: symbol.clone(),
+                    fips,
+                    name,
+                }
+            })
+            .collect();
+        let column: HashMap<String, usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.to_uppercase(), i))
+            .collect();
+
+        let mut cells: BTreeMap<NaiveDate, Vec<Option<f64>>> = BTreeMap::new();
+        for row in rows {
+            let (Some(&col), Some(date)) = (
+                column.get(&row.symbol.to_uppercase()),
+                parse_date(&row.date),
+            ) else {
+                continue;
+            };
+            cells
+                .entry(date)
+                .or_insert_with(|| vec![None; symbols.len()])[col] = row.value;
+        }
+
+        CountyPanel {
+            state: state.to_string(),
+            measure: measure.to_string(),
+            counties,
+            dates: cells.keys().copied().collect(),
+            values: cells.into_values().collect(),
+        }
+    }
+
+    /// CSV with one row per date and one column per county, headed by FIPS
+    /// code (or symbol when the code is unknown).
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("date");
+        for county in &self.counties {
+            out.push(',');
+            out.push_str(county.fips.as_deref().unwrap_or(&county.symbol));
+        }
+        out.push('\n');
+        for (date, row) in self.dates.iter().zip(&self.values) {
+            out.push_str(&date.to_string());
+            for value in row {
+                out.push(',');
+                if let Some(v) = value {
+                    out.push_str(&v.to_string());
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn matches_category(series: &FredSeries, needle: &str) -> bool {
+    series
+        .category
+        .as_deref()
+        .into_iter()
+        .chain(series.name.as_deref())
+        .any(|s| s.to_lowercase().contains(needle))
+}
+
+/// Series of one snapshot listing, fetched a page at a time. An empty page,
+/// or one repeating the previous page, ends the listing; running past
+/// `MAX_PAGES` yields an error.
+pub struct SeriesPages<'a> {
+    browser: &'a FredBrowser,
+    /// Snapshot path without the page number.
+    path: String,
+    page: u32,
+    buffer: std::vec::IntoIter<FredSeries>,
+    last_first: Option<String>,
+    done: bool,
+}
+
+impl Iterator for SeriesPages<'_> {
+    type Item = Result<FredSeries, Box<dyn Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(series) = self.buffer.next() {
+                return Some(Ok(series));
+            }
+            if self.done {
+                return None;
+            }
+            if self.page > MAX_PAGES {
+                self.done = true;
+                return Some(Err(format!(
+                    "{}: more than {} pages of series",
+                    self.path, MAX_PAGES
+                )
+                .into()));
+            }
+            let batch = match get_snapshot_page(
+                self.browser.base_url.clone(),
+                self.browser.client_key.clone(),
+                &self.path,
+                Some(self.page),
+            ) {
+                Ok(batch) => batch,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            // An empty page, or one repeating the previous page, ends the
+            // listing.
+            let first = batch.first().map(|s| s.symbol.clone());
+            if first.is_none() || first == self.last_first {
+                self.done = true;
+                return None;
+            }
+            self.last_first = first;
+            self.page += 1;
+            self.buffer = batch.into_iter();
+        }
+    }
+}
+
+pub struct Walk<'a> {
+    browser: &'a FredBrowser,
+    pending: Vec<FredNode>,
+    current: Option<(FredNode, SeriesPages<'a>)>,
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<(FredNode, FredSeries), Box<dyn Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((node, pages)) = self.current.as_mut() {
+                match pages.next() {
+                    Some(Ok(series)) => return Some(Ok((node.clone(), series))),
+                    Some(Err(e)) => {
+                        self.current = None;
+                        return Some(Err(e));
+                    }
+                    None => self.current = None,
+                }
+            }
+            let node = self.pending.pop()?;
+            match self.browser.children(&node) {
+                Ok(children) => self.pending.extend(children.into_iter().rev()),
+                Err(e) => return Some(Err(e)),
+            }
+            let pages = self.browser.series(&node);
+            self.current = Some((node, pages));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! iCalendar (RFC 5545) export of calendar events.
+//!
+//! Each release becomes a `VEVENT` whose `UID` is derived from its calendar
+//! id alone, so importing a newer export updates the events already in the
+//! calendar rather than adding copies. `SEQUENCE` and `LAST-MODIFIED` follow
+//! the event's last update so clients accept revised values. Releases are
+//! instants: events carry a UTC `DTSTART` and no end.
+
+use crate::calendar::{CalendarEvent, Importance};
+use crate::feed::absolute;
+use crate::timeseries::parse_datetime;
+use chrono::{DateTime, TimeZone, Utc};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const PRODID: &str = "-//Trading Economics//tradingeconomics-rust//EN";
+/// Content lines are folded at 75 octets.
+const LINE_LIMIT: usize = 75;
+
+/// Stable `UID` of the event with calendar id `id`.
+pub fn uid(id: &str) -> String {
+    format!("{}@calendar.tradingeconomics.com", id.trim())
+}
+
+/// Renders `events` as one `VCALENDAR` named `name`. Events without a
+/// readable release time are left out.
+pub fn to_ics(events: &[CalendarEvent], name: &str) -> String {
+    let stamp = ics_time(Utc::now());
+    let mut out = String::new();
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, &format!("PRODID:{}", PRODID));
+    line(&mut out, "CALSCALE:GREGORIAN");
+    line(&mut out, "METHOD:PUBLISH");
+    line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    for event in events {
+        let Some(start) = event.release() else {
+            continue;
+        };
+        let modified = event.last_update.as_deref().and_then(parse_datetime);
+        line(&mut out, "BEGIN:VEVENT");
+        line(&mut out, &format!("UID:{}", uid(&event.calendar_id)));
+        line(&mut out, &format!("DTSTAMP:{}", stamp));
+        line(&mut out, &format!("DTSTART:{}", ics_time(start)));
+        line(&mut out, &format!("SUMMARY:{}", escape(&event.title())));
+        let description = description(event);
+        if !description.is_empty() {
+            line(&mut out, &format!("DESCRIPTION:{}", escape(&description)));
+        }
+        line(
+            &mut out,
+            &format!(
+                "CATEGORIES:{},{}",
+                escape(&event.country),
+                escape(&event.category)
+            ),
+        );
+        if let Some(priority) = event.importance().map(priority) {
+            line(&mut out, &format!("PRIORITY:{}", priority));
+        }
+        if let Some(url) = event.url.as_deref().filter(|u| !u.is_empty()) {
+            line(&mut out, &format!("URL:{}", absolute(url)));
+        }
+        if let Some(modified) = modified {
+            line(&mut out, &format!("LAST-MODIFIED:{}", ics_time(modified)));
+            line(&mut out, &format!("SEQUENCE:{}", sequence(modified)));
+        }
+        line(&mut out, "TRANSP:TRANSPARENT");
+        line(&mut out, "END:VEVENT");
+    }
+    line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Writes `to_ics` to `path`.
+pub fn write_ics<P: AsRef<Path>>(
+    path: P,
+    events: &[CalendarEvent],
+    name: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fs::write(path, to_ics(events, name))?;
+    Ok(())
+}
+
+/// Published values one per line, then the reference period and source.
+fn description(event: &CalendarEvent) -> String {
+    let mut lines = event.value_lines();
+    if let Some(reference) = event.reference.as_deref().filter(|r| !r.is_empty()) {
+        lines.push(format!("Reference: {}", reference));
+    }
+    if let Some(source) = event.source.as_deref().filter(|s| !s.is_empty()) {
+        lines.push(format!("Source: {}", source));
+    }
+    lines.join("\n")
+}
+
+/// RFC 5545 priority: 1 is highest, 9 lowest.
+fn priority(importance: Importance) -> u8 {
+    match importance {
+        Importance::High => 1,
+        Importance::Medium => 5,
+        Importance::Low => 9,
+    }
+}
+
+/// Minutes since 2000 of the last update; grows with every revision and
+/// fits the 32-bit integers clients store it in.
+fn sequence(modified: DateTime<Utc>) -> i64 {
+    let epoch = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+    (modified - epoch).num_minutes().max(0)
+}
+
+fn ics_time(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends a content line, folded at `LINE_LIMIT` octets without splitting
+/// a UTF-8 character, and ended with CRLF.
+fn line(out: &mut String, content: &str) {
+    let mut width = 0;
+    for c in content.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            out.push_str("\r\n ");
+            // The leading space of a continuation line counts.
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,31 @@
+use crate::api::{encode, get_json};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// One observation returned by `/historical/country/{country}/indicator/{indicator}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HistoricalData {
+    pub country: String,
+    pub category: String,
+    pub date_time: String,
+    pub value: Option<f64>,
+    pub frequency: Option<String>,
+    pub historical_data_symbol: Option<String>,
+    pub last_update: Option<String>,
+}
+
+pub fn get_historical_country_indicator(
+    base_url: String,
+    client_key: String,
+    country: &str,
+    indicator: &str,
+) -> Result<Vec<HistoricalData>, Box<dyn Error + Send + Sync>> {
+    let path = format!(
+        "/historical/country/{}/indicator/{}",
+        encode(country),
+        encode(indicator)
+    );
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
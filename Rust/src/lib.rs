@@ -1 +1,22 @@
 pub use comtrade::*;
+
+mod api;
+pub mod calendar;
+pub mod earnings;
+pub mod eurostat;
+pub mod feed;
+pub mod financials;
+pub mod forecast;
+pub mod fred;
+pub mod ics;
+pub mod indicators;
+pub mod markets;
+pub mod news;
+pub mod ratings;
+pub mod resolve;
+pub mod screen;
+pub mod search;
+pub mod sentiment;
+pub mod storage;
+pub mod timeseries;
+pub mod worldbank;
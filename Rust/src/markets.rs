@@ -0,0 +1,123 @@
+use crate::api::{encode, get_json};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// One daily bar returned by `/markets/historical/{symbol}`. Prices are
+/// missing on days a thinly traded symbol did not trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarketBar {
+    pub symbol: String,
+    pub date: String,
+    #[serde(default)]
+    pub open: Option<f64>,
+    #[serde(default)]
+    pub high: Option<f64>,
+    #[serde(default)]
+    pub low: Option<f64>,
+    #[serde(default)]
+    pub close: Option<f64>,
+}
+
+pub fn get_market_historical(
+    base_url: String,
+    client_key: String,
+    symbol: &str,
+) -> Result<Vec<MarketBar>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/markets/historical/{}", encode(symbol));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// One row of `/markets/forecasts/...`: the last price and projections for
+/// the next four quarter ends.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarketForecast {
+    pub symbol: String,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "Type")]
+    pub market_type: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub last: Option<f64>,
+    #[serde(default)]
+    pub forecast1: Option<f64>,
+    #[serde(default)]
+    pub forecast2: Option<f64>,
+    #[serde(default)]
+    pub forecast3: Option<f64>,
+    #[serde(default)]
+    pub forecast4: Option<f64>,
+    #[serde(default, alias = "ForecastDate1")]
+    pub forecast1_date: Option<String>,
+    #[serde(default, alias = "ForecastDate2")]
+    pub forecast2_date: Option<String>,
+    #[serde(default, alias = "ForecastDate3")]
+    pub forecast3_date: Option<String>,
+    #[serde(default, alias = "ForecastDate4")]
+    pub forecast4_date: Option<String>,
+}
+
+/// Forecasts for a market category such as `index`, `currency`, `bond` or
+/// `commodity`.
+pub fn get_market_forecasts(
+    base_url: String,
+    client_key: String,
+    category: &str,
+) -> Result<Vec<MarketForecast>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/markets/forecasts/{}", encode(category));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_market_forecasts_symbol(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+) -> Result<Vec<MarketForecast>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/markets/forecasts/symbol/{}", encode(&symbols.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// One constituent returned by `/markets/components/{symbols}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarketComponent {
+    pub symbol: String,
+    #[serde(default)]
+    pub ticker: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default, rename = "Type")]
+    pub market_type: Option<String>,
+    #[serde(default)]
+    pub sector: Option<String>,
+    #[serde(default)]
+    pub industry: Option<String>,
+    #[serde(default)]
+    pub last: Option<f64>,
+    #[serde(default)]
+    pub market_cap: Option<f64>,
+    /// How the row relates to the requested symbol, e.g. `component`.
+    #[serde(default)]
+    pub relationship: Option<String>,
+}
+
+/// Constituents of one or more indexes such as `psi20:ind`.
+pub fn get_market_components(
+    base_url: String,
+    client_key: String,
+    symbols: &[&str],
+) -> Result<Vec<MarketComponent>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/markets/components/{}", encode(&symbols.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
@@ -0,0 +1,304 @@
+//! News headlines from `/news/...` and a poller that follows them.
+//!
+//! `/news` lists the latest items first and has no "since" parameter, so
+//! `NewsPoller` re-reads the first page on every tick, drops ids it has
+//! already emitted, and keeps the newest id seen in a cursor file so a
+//! restarted feed picks up where it stopped instead of replaying the page.
+
+use crate::api::{encode, get_json, string_or_number};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NewsItem {
+    #[serde(deserialize_with = "string_or_number")]
+    pub id: String,
+    pub title: String,
+    pub date: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub importance: Option<i32>,
+}
+
+impl NewsItem {
+    /// Ids are increasing integers sent as strings.
+    pub fn numeric_id(&self) -> Option<u64> {
+        self.id.trim().parse().ok()
+    }
+}
+
+/// Latest news; `start` skips that many items and `limit` caps the page.
+pub fn get_news(
+    base_url: String,
+    client_key: String,
+    limit: Option<u32>,
+    start: Option<u32>,
+) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/news");
+    let url = paged_url(&base_url, &path, &client_key, limit, start);
+    get_json(url)
+}
+
+pub fn get_news_country(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+    limit: Option<u32>,
+    start: Option<u32>,
+) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/news/country/{}", encode(&countries.join(",")));
+    let url = paged_url(&base_url, &path, &client_key, limit, start);
+    get_json(url)
+}
+
+pub fn get_news_indicator(
+    base_url: String,
+    client_key: String,
+    indicators: &[&str],
+    limit: Option<u32>,
+    start: Option<u32>,
+) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/news/indicator/{}", encode(&indicators.join(",")));
+    let url = paged_url(&base_url, &path, &client_key, limit, start);
+    get_json(url)
+}
+
+pub fn get_news_country_indicator(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+    indicators: &[&str],
+    limit: Option<u32>,
+    start: Option<u32>,
+) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+    let path = format!(
+        "/news/country/{}/{}",
+        encode(&countries.join(",")),
+        encode(&indicators.join(","))
+    );
+    let url = paged_url(&base_url, &path, &client_key, limit, start);
+    get_json(url)
+}
+
+fn paged_url(
+    base_url: &str,
+    path: &str,
+    client_key: &str,
+    limit: Option<u32>,
+    start: Option<u32>,
+) -> String {
+    let mut url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    if let Some(limit) = limit {
+        url = format!("{}&limit={}", url, limit);
+    }
+    if let Some(start) = start {
+        url = format!("{}&start={}", url, start);
+    }
+    url
+}
+
+/// Which `/news` listing a poller follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NewsFeed {
+    All,
+    Countries(Vec<String>),
+    Indicators(Vec<String>),
+    CountryIndicator {
+        countries: Vec<String>,
+        indicators: Vec<String>,
+    },
+}
+
+/// Re-fetches a news listing and yields only items it has not emitted yet.
+pub struct NewsPoller {
+    base_url: String,
+    client_key: String,
+    feed: NewsFeed,
+    interval: Duration,
+    limit: Option<u32>,
+    cursor_path: Option<PathBuf>,
+    newest: Option<u64>,
+    seen: HashSet<String>,
+    skip_backlog: bool,
+    /// Set once a fetch has succeeded, whatever it returned.
+    polled: bool,
+}
+
+/// The background thread of `NewsPoller::spawn`.
+pub type PollerHandle = JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>;
+
+/// Ids remembered against repeats within a page or across polls before the
+/// set is reset; the numeric cursor covers everything older.
+const SEEN_CAPACITY: usize = 10_000;
+
+impl NewsPoller {
+    pub fn new(base_url: &str, client_key: &str, feed: NewsFeed) -> NewsPoller {
+        NewsPoller {
+            base_url: base_url.to_string(),
+            client_key: client_key.to_string(),
+            feed,
+            interval: Duration::from_secs(60),
+            limit: None,
+            cursor_path: None,
+            newest: None,
+            seen: HashSet::new(),
+            skip_backlog: false,
+            polled: false,
+        }
+    }
+
+    /// Time between fetches in `run` and `spawn`; one minute by default.
+    pub fn interval(mut self, interval: Duration) -> NewsPoller {
+        self.interval = interval;
+        self
+    }
+
+    /// Page size asked of the API on every fetch.
+    pub fn limit(mut self, limit: u32) -> NewsPoller {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Persists the newest id to `path`, loading it first if the file
+    /// exists.
+    pub fn cursor_file<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+    ) -> Result<NewsPoller, Box<dyn Error + Send + Sync>> {
+        let path = path.into();
+        if path.exists() {
+            let text = fs::read_to_string(&path)?;
+            let text = text.trim();
+            if !text.is_empty() {
+                self.newest = Some(
+                    text.parse()
+                        .map_err(|_| format!("bad news cursor in {}", path.display()))?,
+                );
+            }
+        }
+        self.cursor_path = Some(path);
+        Ok(self)
+    }
+
+    /// Without a stored cursor, the first fetch only records the newest id
+    /// instead of emitting the whole page.
+    pub fn skip_backlog(mut self) -> NewsPoller {
+        self.skip_backlog = true;
+        self
+    }
+
+    /// The newest id emitted so far, or loaded from the cursor file.
+    pub fn cursor(&self) -> Option<u64> {
+        self.newest
+    }
+
+    /// Fetches once and returns the new items, oldest first.
+    pub fn poll_once(&mut self) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+        let items = self.fetch()?;
+        let first_run = !self.polled && self.newest.is_none();
+        self.polled = true;
+        if self.seen.len() > SEEN_CAPACITY {
+            self.seen.clear();
+        }
+        let mut fresh = Vec::new();
+        for item in items {
+            let is_new = match (item.numeric_id(), self.newest) {
+                (Some(id), Some(newest)) => id > newest,
+                _ => true,
+            };
+            if is_new && self.seen.insert(item.id.clone()) {
+                fresh.push(item);
+            }
+        }
+        fresh.sort_by_key(|item| item.numeric_id());
+        let newest = fresh.iter().filter_map(NewsItem::numeric_id).max();
+        if newest > self.newest {
+            self.newest = newest;
+            self.save_cursor()?;
+        }
+        if first_run && self.skip_backlog {
+            return Ok(Vec::new());
+        }
+        Ok(fresh)
+    }
+
+    /// Polls forever, handing every new item to `on_item`. Returns the first
+    /// fetch or cursor error.
+    pub fn run<F: FnMut(NewsItem)>(
+        &mut self,
+        mut on_item: F,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        loop {
+            for item in self.poll_once()? {
+                on_item(item);
+            }
+            thread::sleep(self.interval);
+        }
+    }
+
+    /// Polls on a background thread and sends new items over a channel. The
+    /// thread ends when the receiver is dropped or a fetch fails; its result
+    /// is available through the handle.
+    pub fn spawn(mut self) -> (Receiver<NewsItem>, PollerHandle) {
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || loop {
+            for item in self.poll_once()? {
+                if tx.send(item).is_err() {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        });
+        (rx, handle)
+    }
+
+    fn fetch(&self) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+        let (base_url, client_key) = (self.base_url.clone(), self.client_key.clone());
+        match &self.feed {
+            NewsFeed::All => get_news(base_url, client_key, self.limit, None),
+            NewsFeed::Countries(countries) => {
+                get_news_country(base_url, client_key, &refs(countries), self.limit, None)
+            }
+            NewsFeed::Indicators(indicators) => {
+                get_news_indicator(base_url, client_key, &refs(indicators), self.limit, None)
+            }
+            NewsFeed::CountryIndicator {
+                countries,
+                indicators,
+            } => get_news_country_indicator(
+                base_url,
+                client_key,
+                &refs(countries),
+                &refs(indicators),
+                self.limit,
+                None,
+            ),
+        }
+    }
+
+    fn save_cursor(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let (Some(path), Some(newest)) = (&self.cursor_path, self.newest) {
+            fs::write(path, newest.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+fn refs(values: &[String]) -> Vec<&str> {
+    values.iter().map(String::as_str).collect()
+}
@@ -0,0 +1,651 @@
+//! Sovereign credit ratings from `/ratings/...`.
+//!
+//! Every agency scale maps onto one common notch scale, AAA = 1 down to
+//! default = 22, so ratings from S&P, Moody's, Fitch and DBRS can be
+//! compared and averaged. BBB- (Baa3, BBB (low)) = 10 is the last
+//! investment-grade notch. An outlook or watch shifts the notch by a
+//! fraction in `adjusted`: a third of a notch for an outlook, two thirds for
+//! a watch, towards the side it points to.
+//!
+//! A rating history yields the upgrades and downgrades between consecutive
+//! actions of an agency, and cohort transition matrices between letter
+//! grades over any horizon. Withdrawals stay in the history: no change is
+//! reported across one, and a withdrawn issuer leaves the cohorts.
+
+use crate::api::{encode, get_json};
+use crate::timeseries::parse_date;
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Notch of the lowest investment-grade rating.
+pub const LAST_INVESTMENT_GRADE: u8 = 10;
+/// Notch of a default.
+pub const DEFAULT_NOTCH: u8 = 22;
+
+/// S&P and Fitch letters, best first; the index plus one is the notch.
+const LETTER_SCALE: [&str; 21] = [
+    "AAA", "AA+", "AA", "AA-", "A+", "A", "A-", "BBB+", "BBB", "BBB-", "BB+", "BB", "BB-", "B+",
+    "B", "B-", "CCC+", "CCC", "CCC-", "CC", "C",
+];
+
+const MOODYS_SCALE: [&str; 21] = [
+    "Aaa", "Aa1", "Aa2", "Aa3", "A1", "A2", "A3", "Baa1", "Baa2", "Baa3", "Ba1", "Ba2", "Ba3",
+    "B1", "B2", "B3", "Caa1", "Caa2", "Caa3", "Ca", "C",
+];
+
+/// DBRS grades, each of which but AAA, CC and C has `(high)` and `(low)`
+/// notches around it.
+const DBRS_GRADES: [&str; 9] = ["AAA", "AA", "A", "BBB", "BB", "B", "CCC", "CC", "C"];
+
+/// One agency action returned by `/ratings/historical/{countries}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HistoricalRating {
+    pub country: String,
+    pub date: String,
+    pub agency: String,
+    pub rating: String,
+    pub outlook: Option<String>,
+}
+
+/// One row of `/ratings` or `/ratings/{countries}`: the current rating and
+/// outlook from each agency, blank where an agency does not rate the
+/// country.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CountryRatings {
+    #[serde(rename = "Country")]
+    pub country: String,
+    /// Trading Economics' own 0–100 credit score.
+    #[serde(default, rename = "TE")]
+    pub te: Option<String>,
+    #[serde(default, rename = "TE_Outlook")]
+    pub te_outlook: Option<String>,
+    #[serde(default, rename = "SP")]
+    pub sp: Option<String>,
+    #[serde(default, rename = "SP_Outlook")]
+    pub sp_outlook: Option<String>,
+    #[serde(default, rename = "Moodys")]
+    pub moodys: Option<String>,
+    #[serde(default, rename = "Moodys_Outlook")]
+    pub moodys_outlook: Option<String>,
+    #[serde(default, rename = "Fitch")]
+    pub fitch: Option<String>,
+    #[serde(default, rename = "Fitch_Outlook")]
+    pub fitch_outlook: Option<String>,
+    #[serde(default, rename = "DBRS")]
+    pub dbrs: Option<String>,
+    #[serde(default, rename = "DBRS_Outlook")]
+    pub dbrs_outlook: Option<String>,
+}
+
+impl CountryRatings {
+    /// The agency ratings that map onto the common scale.
+    pub fn ratings(&self) -> Vec<SovereignRating> {
+        [
+            (Agency::SP, &self.sp, &self.sp_outlook),
+            (Agency::Moodys, &self.moodys, &self.moodys_outlook),
+            (Agency::Fitch, &self.fitch, &self.fitch_outlook),
+            (Agency::Dbrs, &self.dbrs, &self.dbrs_outlook),
+        ]
+        .into_iter()
+        .filter_map(|(agency, rating, outlook)| {
+            SovereignRating::new(
+                &self.country,
+                agency,
+                rating.as_deref()?,
+                outlook.as_deref(),
+                None,
+            )
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Agency {
+    SP,
+    Moodys,
+    Fitch,
+    Dbrs,
+}
+
+impl Agency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Agency::SP => "S&P",
+            Agency::Moodys => "Moody's",
+            Agency::Fitch => "Fitch",
+            Agency::Dbrs => "DBRS",
+        }
+    }
+
+    /// The common notch of `rating` on this agency's scale.
+    pub fn notch(&self, rating: &str) -> Option<u8> {
+        let rating = rating.trim();
+        let position = |scale: &[&str]| scale.iter().position(|r| *r == rating);
+        let notch = match self {
+            Agency::SP | Agency::Fitch => match rating {
+                "SD" | "RD" | "D" | "DDD" | "DD" => return Some(DEFAULT_NOTCH),
+                _ => position(&LETTER_SCALE)?,
+            },
+            Agency::Moodys => position(&MOODYS_SCALE)?,
+            Agency::Dbrs => return dbrs_notch(rating),
+        };
+        Some(notch as u8 + 1)
+    }
+
+    /// This agency's rating for a common notch.
+    pub fn label(&self, notch: u8) -> Option<String> {
+        let i = usize::from(notch.checked_sub(1)?);
+        match self {
+            Agency::SP | Agency::Fitch if notch == DEFAULT_NOTCH => Some("D".to_string()),
+            Agency::SP | Agency::Fitch => LETTER_SCALE.get(i).map(|r| r.to_string()),
+            Agency::Moodys => MOODYS_SCALE.get(i).map(|r| r.to_string()),
+            Agency::Dbrs if notch == DEFAULT_NOTCH => Some("D".to_string()),
+            Agency::Dbrs => dbrs_labels()
+                .into_iter()
+                .find(|(_, n)| *n == notch)
+                .map(|(label, _)| label),
+        }
+    }
+}
+
+impl FromStr for Agency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key: String = s
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match key.as_str() {
+            "sp" | "standardpoors" | "standardandpoors" | "spglobal" => Ok(Agency::SP),
+            "moodys" => Ok(Agency::Moodys),
+            "fitch" => Ok(Agency::Fitch),
+            "dbrs" | "dbrsmorningstar" => Ok(Agency::Dbrs),
+            _ => Err(format!("unknown rating agency: {}", s.trim())),
+        }
+    }
+}
+
+impl fmt::Display for Agency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn dbrs_labels() -> Vec<(String, u8)> {
+    let mut labels = Vec::new();
+    let mut notch = 1;
+    for grade in DBRS_GRADES {
+        if matches!(grade, "AAA" | "CC" | "C") {
+            labels.push((grade.to_string(), notch));
+            notch += 1;
+        } else {
+            for suffix in [" (high)", "", " (low)"] {
+                labels.push((format!("{}{}", grade, suffix), notch));
+                notch += 1;
+            }
+        }
+    }
+    labels
+}
+
+fn dbrs_notch(rating: &str) -> Option<u8> {
+    let rating = rating.to_lowercase();
+    if matches!(rating.as_str(), "d" | "sd" | "rd") {
+        return Some(DEFAULT_NOTCH);
+    }
+    let (grade, suffix) = match rating.split_once('(') {
+        Some((g, s)) => (g.trim(), s.trim_end_matches(')').trim()),
+        None => (rating.trim(), ""),
+    };
+    let suffix = match suffix {
+        "high" | "h" => " (high)",
+        "low" | "l" => " (low)",
+        "" => "",
+        _ => return None,
+    };
+    let label = format!("{}{}", grade.to_uppercase(), suffix);
+    dbrs_labels()
+        .into_iter()
+        .find(|(l, _)| *l == label)
+        .map(|(_, n)| n)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outlook {
+    Positive,
+    Stable,
+    Negative,
+    Developing,
+    WatchPositive,
+    WatchNegative,
+    WatchDeveloping,
+}
+
+impl Outlook {
+    /// `None` for blank or unrecognised text.
+    pub fn parse(s: &str) -> Option<Outlook> {
+        let s = s.trim().to_lowercase();
+        let watch = s.contains("watch") || s.contains("review");
+        // The direction words come first: "possible downgrade" contains
+        // "pos".
+        let (plain, on_watch) = if s.contains("downgrade") {
+            (Outlook::Negative, Outlook::WatchNegative)
+        } else if s.contains("upgrade") {
+            (Outlook::Positive, Outlook::WatchPositive)
+        } else if s.contains("neg") {
+            (Outlook::Negative, Outlook::WatchNegative)
+        } else if s.contains("pos") {
+            (Outlook::Positive, Outlook::WatchPositive)
+        } else if s.contains("dev") || s.contains("evolving") {
+            (Outlook::Developing, Outlook::WatchDeveloping)
+        } else if s.contains("stable") {
+            (Outlook::Stable, Outlook::Stable)
+        } else {
+            return None;
+        };
+        Some(if watch { on_watch } else { plain })
+    }
+
+    /// Notch shift: negative moves towards AAA.
+    pub fn adjustment(&self) -> f64 {
+        match self {
+            Outlook::Positive => -1.0 / 3.0,
+            Outlook::Negative => 1.0 / 3.0,
+            Outlook::WatchPositive => -2.0 / 3.0,
+            Outlook::WatchNegative => 2.0 / 3.0,
+            Outlook::Stable | Outlook::Developing | Outlook::WatchDeveloping => 0.0,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outlook::Positive => "Positive",
+            Outlook::Stable => "Stable",
+            Outlook::Negative => "Negative",
+            Outlook::Developing => "Developing",
+            Outlook::WatchPositive => "Watch Positive",
+            Outlook::WatchNegative => "Watch Negative",
+            Outlook::WatchDeveloping => "Watch Developing",
+        }
+    }
+}
+
+/// One agency's rating of one country, placed on the common scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SovereignRating {
+    pub country: String,
+    pub agency: Agency,
+    /// The rating as the agency writes it.
+    pub rating: String,
+    pub outlook: Option<Outlook>,
+    pub notch: u8,
+    /// Date of the action, for historical records.
+    pub date: Option<NaiveDate>,
+}
+
+impl SovereignRating {
+    /// `None` when `rating` is blank or not on the agency's scale.
+    pub fn new(
+        country: &str,
+        agency: Agency,
+        rating: &str,
+        outlook: Option<&str>,
+        date: Option<NaiveDate>,
+    ) -> Option<SovereignRating> {
+        Some(SovereignRating {
+            country: country.to_string(),
+            agency,
+            rating: rating.trim().to_string(),
+            outlook: outlook.and_then(Outlook::parse),
+            notch: agency.notch(rating)?,
+            date,
+        })
+    }
+
+    /// `None` for an unknown agency or an off-scale rating; see
+    /// `RatingAction::from_historical` for withdrawals.
+    pub fn from_historical(row: &HistoricalRating) -> Option<SovereignRating> {
+        SovereignRating::new(
+            &row.country,
+            row.agency.parse().ok()?,
+            &row.rating,
+            row.outlook.as_deref(),
+            parse_date(&row.date),
+        )
+    }
+
+    /// The notch shifted by the outlook.
+    pub fn adjusted(&self) -> f64 {
+        f64::from(self.notch) + self.outlook.map_or(0.0, |o| o.adjustment())
+    }
+
+    pub fn is_investment_grade(&self) -> bool {
+        self.notch <= LAST_INVESTMENT_GRADE
+    }
+}
+
+/// Whether a historical rating records the withdrawal of the agency's
+/// rating rather than a rating (`WD`, `WR`, `NR`, "withdrawn", ...).
+pub fn is_withdrawal(rating: &str) -> bool {
+    let r: String = rating
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ')
+        .collect();
+    matches!(r.as_str(), "wd" | "wr" | "nr" | "withdrawn" | "not rated")
+}
+
+/// One entry of an agency's rating history of a country.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RatingAction {
+    Rated(SovereignRating),
+    /// The agency stopped rating the country.
+    Withdrawn {
+        country: String,
+        agency: Agency,
+        date: NaiveDate,
+    },
+}
+
+impl RatingAction {
+    /// `None` for an unknown agency, an undated withdrawal, or a rating that
+    /// is neither on the agency's scale nor a withdrawal.
+    pub fn from_historical(row: &HistoricalRating) -> Option<RatingAction> {
+        if is_withdrawal(&row.rating) {
+            return Some(RatingAction::Withdrawn {
+                country: row.country.clone(),
+                agency: row.agency.parse().ok()?,
+                date: parse_date(&row.date)?,
+            });
+        }
+        SovereignRating::from_historical(row).map(RatingAction::Rated)
+    }
+
+    pub fn country(&self) -> &str {
+        match self {
+            RatingAction::Rated(r) => &r.country,
+            RatingAction::Withdrawn { country, .. } => country,
+        }
+    }
+
+    pub fn agency(&self) -> Agency {
+        match self {
+            RatingAction::Rated(r) => r.agency,
+            RatingAction::Withdrawn { agency, .. } => *agency,
+        }
+    }
+
+    pub fn date(&self) -> Option<NaiveDate> {
+        match self {
+            RatingAction::Rated(r) => r.date,
+            RatingAction::Withdrawn { date, .. } => Some(*date),
+        }
+    }
+
+    /// The rating, unless this is a withdrawal.
+    pub fn rating(&self) -> Option<&SovereignRating> {
+        match self {
+            RatingAction::Rated(r) => Some(r),
+            RatingAction::Withdrawn { .. } => None,
+        }
+    }
+}
+
+/// Agencies' ratings of one country combined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consensus {
+    pub country: String,
+    pub agencies: usize,
+    pub mean_notch: f64,
+    /// Mean of the outlook-adjusted notches.
+    pub mean_adjusted: f64,
+    pub best: u8,
+    pub worst: u8,
+    /// Investment grade on the mean notch rounded to the nearest notch.
+    pub investment_grade: bool,
+    /// Some agencies rate the country investment grade and others do not.
+    pub split: bool,
+}
+
+impl Consensus {
+    /// `None` when `ratings` is empty. Callers pass one rating per agency
+    /// for one country; the country is taken from the first.
+    pub fn of(ratings: &[SovereignRating]) -> Option<Consensus> {
+        let first = ratings.first()?;
+        let n = ratings.len() as f64;
+        let mean_notch = ratings.iter().map(|r| f64::from(r.notch)).sum::<f64>() / n;
+        let mean_adjusted = ratings.iter().map(SovereignRating::adjusted).sum::<f64>() / n;
+        let grades = ratings.iter().filter(|r| r.is_investment_grade()).count();
+        Some(Consensus {
+            country: first.country.clone(),
+            agencies: ratings.len(),
+            mean_notch,
+            mean_adjusted,
+            best: ratings.iter().map(|r| r.notch).min()?,
+            worst: ratings.iter().map(|r| r.notch).max()?,
+            investment_grade: mean_notch.round() <= f64::from(LAST_INVESTMENT_GRADE),
+            split: grades > 0 && grades < ratings.len(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Upgrade,
+    Downgrade,
+}
+
+/// A move of one agency's rating of one country between two actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatingChange {
+    pub country: String,
+    pub agency: Agency,
+    pub date: NaiveDate,
+    pub from: String,
+    pub to: String,
+    pub from_notch: u8,
+    pub to_notch: u8,
+    /// Notches gained: positive for an upgrade.
+    pub delta: i8,
+    pub direction: Direction,
+    pub outlook: Option<Outlook>,
+}
+
+/// Every upgrade and downgrade in `actions`, in date order. Actions that
+/// only change the outlook, and undated ratings, are skipped, as is a
+/// re-rating after a withdrawal.
+pub fn rating_changes(actions: &[RatingAction]) -> Vec<RatingChange> {
+    let mut changes = Vec::new();
+    for history in by_issuer(actions).values() {
+        for pair in history.windows(2) {
+            let (Some(prev), Some(next)) = (pair[0].rating(), pair[1].rating()) else {
+                continue;
+            };
+            let (Some(date), true) = (next.date, prev.notch != next.notch) else {
+                continue;
+            };
+            let delta = prev.notch as i8 - next.notch as i8;
+            changes.push(RatingChange {
+                country: next.country.clone(),
+                agency: next.agency,
+                date,
+                from: prev.rating.clone(),
+                to: next.rating.clone(),
+                from_notch: prev.notch,
+                to_notch: next.notch,
+                delta,
+                direction: if delta > 0 {
+                    Direction::Upgrade
+                } else {
+                    Direction::Downgrade
+                },
+                outlook: next.outlook,
+            });
+        }
+    }
+    changes.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then_with(|| a.country.cmp(&b.country))
+            .then(a.agency.cmp(&b.agency))
+    });
+    changes
+}
+
+/// Letter grades the transition matrix counts between, best first.
+pub const GRADES: [&str; 8] = ["AAA", "AA", "A", "BBB", "BB", "B", "CCC/C", "D"];
+
+/// Index into `GRADES` of a common notch.
+pub fn grade(notch: u8) -> usize {
+    match notch {
+        0..=1 => 0,
+        2..=4 => 1,
+        5..=7 => 2,
+        8..=10 => 3,
+        11..=13 => 4,
+        14..=16 => 5,
+        17..=21 => 6,
+        _ => 7,
+    }
+}
+
+/// Counts of grade moves over a fixed horizon; rows are the grade at the
+/// start, columns the grade at the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionMatrix {
+    pub horizon_months: u32,
+    pub counts: [[usize; 8]; 8],
+}
+
+impl TransitionMatrix {
+    /// Observations starting in each grade.
+    pub fn row_totals(&self) -> [usize; 8] {
+        self.counts.map(|row| row.iter().sum())
+    }
+
+    /// Share of each row ending in each grade; `None` for a grade never
+    /// observed at the start.
+    pub fn probabilities(&self) -> [[Option<f64>; 8]; 8] {
+        let totals = self.row_totals();
+        let mut out = [[None; 8]; 8];
+        for (i, row) in self.counts.iter().enumerate() {
+            if totals[i] == 0 {
+                continue;
+            }
+            for (j, n) in row.iter().enumerate() {
+                out[i][j] = Some(*n as f64 / totals[i] as f64);
+            }
+        }
+        out
+    }
+}
+
+/// Cohort transition matrix over `horizon_months`. A cohort is formed every
+/// `step_months` from the first dated action until `until` less the
+/// horizon; each agency–country pair rated at the cohort date contributes
+/// the move from its grade then to its grade a horizon later. A rating is
+/// taken to stand until the agency's next action; pairs withdrawn within
+/// the horizon are left out of that cohort.
+pub fn transition_matrix(
+    actions: &[RatingAction],
+    horizon_months: u32,
+    step_months: u32,
+    until: NaiveDate,
+) -> TransitionMatrix {
+    let mut matrix = TransitionMatrix {
+        horizon_months,
+        counts: [[0; 8]; 8],
+    };
+    let histories = by_issuer(actions);
+    let Some(first) = histories.values().filter_map(|h| h[0].date()).min() else {
+        return matrix;
+    };
+    let (horizon, step) = (Months::new(horizon_months), Months::new(step_months.max(1)));
+    let mut start = first;
+    while let Some(end) = start.checked_add_months(horizon).filter(|e| *e <= until) {
+        for history in histories.values() {
+            if withdrawn_between(history, start, end) {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (standing(history, start), standing(history, end)) {
+                matrix.counts[grade(a)][grade(b)] += 1;
+            }
+        }
+        let Some(next) = start.checked_add_months(step) else {
+            break;
+        };
+        start = next;
+    }
+    matrix
+}
+
+/// Dated actions per agency and country, oldest first.
+fn by_issuer(actions: &[RatingAction]) -> BTreeMap<(String, Agency), Vec<&RatingAction>> {
+    let mut out: BTreeMap<(String, Agency), Vec<&RatingAction>> = BTreeMap::new();
+    for a in actions.iter().filter(|a| a.date().is_some()) {
+        out.entry((a.country().to_lowercase(), a.agency()))
+            .or_default()
+            .push(a);
+    }
+    for history in out.values_mut() {
+        history.sort_by_key(|a| a.date());
+    }
+    out
+}
+
+/// The notch in force on `date`; `None` before the first rating and while
+/// withdrawn.
+fn standing(history: &[&RatingAction], date: NaiveDate) -> Option<u8> {
+    history
+        .iter()
+        .take_while(|a| a.date().is_some_and(|d| d <= date))
+        .last()
+        .and_then(|a| a.rating())
+        .map(|r| r.notch)
+}
+
+/// Whether a withdrawal falls in `(start, end]`.
+fn withdrawn_between(history: &[&RatingAction], start: NaiveDate, end: NaiveDate) -> bool {
+    history
+        .iter()
+        .any(|a| matches!(a, RatingAction::Withdrawn { date, .. } if *date > start && *date <= end))
+}
+
+/// Current ratings of every country.
+pub fn get_ratings(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<CountryRatings>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/ratings");
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_ratings_country(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+) -> Result<Vec<CountryRatings>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/ratings/{}", encode(&countries.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_ratings_historical(
+    base_url: String,
+    client_key: String,
+    countries: &[&str],
+) -> Result<Vec<HistoricalRating>, Box<dyn Error + Send + Sync>> {
+    let path = format!("/ratings/historical/{}", encode(&countries.join(",")));
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
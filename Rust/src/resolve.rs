@@ -0,0 +1,290 @@
+//! Resolution of tradingeconomics.com page URLs to API series.
+//!
+//! Analysts paste site links such as
+//! `https://tradingeconomics.com/united-states/gdp-growth-rate` or
+//! `/united-states/real-interest-rate-percent-wb-data.html`. The page path
+//! tells which dataset a link belongs to; the resolver then looks the path up
+//! in that dataset's snapshot (every snapshot row carries its page `URL`) to
+//! find the symbol, and downloads the history behind it.
+
+use crate::api::{encode, get_json};
+use crate::eurostat::get_eurostat_historical;
+use crate::fred::get_fred_historical;
+use crate::indicators::get_historical_country_indicator;
+use crate::markets::get_market_historical;
+use crate::timeseries::{parse_date, Frequency, TimeSeries};
+use crate::worldbank::{get_wb_historical, WbSymbol};
+use serde_json::{Map, Value};
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dataset {
+    Indicator,
+    Market,
+    Fred,
+    WorldBank,
+    Eurostat,
+}
+
+/// The API identifier a page resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TeSymbol {
+    Indicator { country: String, category: String },
+    Market(String),
+    Fred(String),
+    WorldBank(String),
+    Eurostat(String),
+}
+
+impl TeSymbol {
+    pub fn dataset(&self) -> Dataset {
+        match self {
+            TeSymbol::Indicator { .. } => Dataset::Indicator,
+            TeSymbol::Market(_) => Dataset::Market,
+            TeSymbol::Fred(_) => Dataset::Fred,
+            TeSymbol::WorldBank(_) => Dataset::WorldBank,
+            TeSymbol::Eurostat(_) => Dataset::Eurostat,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub symbol: TeSymbol,
+    pub name: Option<String>,
+    pub latest_value: Option<f64>,
+    pub latest_date: Option<String>,
+    /// The snapshot row the page matched, as returned by the API.
+    pub snapshot: Map<String, Value>,
+    pub history: TimeSeries,
+}
+
+/// Splits a tradingeconomics.com link into its dataset and page path
+/// (`/country/slug`), without calling the API.
+pub fn classify(url: &str) -> Result<(Dataset, String), String> {
+    let mut rest = url.trim();
+    for scheme in ["https://", "http://"] {
+        if let Some(stripped) = rest.strip_prefix(scheme) {
+            rest = stripped;
+        }
+    }
+    if !rest.starts_with('/') {
+        let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let host = host.to_lowercase();
+        if host != "tradingeconomics.com" && !host.ends_with(".tradingeconomics.com") {
+            return Err(format!("not a tradingeconomics.com url: {}", url));
+        }
+        rest = path;
+    }
+    let path = rest.split(['?', '#']).next().unwrap_or("");
+    let path = path.trim_end_matches('/').to_lowercase();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        return Err(format!("no page path in url: {}", url));
+    }
+    let last = segments[segments.len() - 1];
+    let dataset = if last.ends_with("-fed-data.html") {
+        Dataset::Fred
+    } else if last.ends_with("-wb-data.html") {
+        Dataset::WorldBank
+    } else if last.ends_with("-eurostat-data.html") {
+        Dataset::Eurostat
+    } else if last.contains(':') || matches!(segments[0], "commodity" | "crypto") {
+        Dataset::Market
+    } else if segments.len() == 2 {
+        Dataset::Indicator
+    } else {
+        return Err(format!("unrecognised tradingeconomics.com page: {}", url));
+    };
+    Ok((dataset, format!("/{}", segments.join("/"))))
+}
+
+/// Resolves a page link to its symbol, latest snapshot and history.
+pub fn resolve_url(
+    base_url: String,
+    client_key: String,
+    url: &str,
+) -> Result<Resolved, Box<dyn Error + Send + Sync>> {
+    let (dataset, path) = classify(url)?;
+    let country = path
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .replace('-', " ");
+    let not_found = || format!("no {:?} series found for {}", dataset, path);
+
+    let (symbol, snapshot) = match dataset {
+        Dataset::Indicator => {
+            let rows: Vec<Map<String, Value>> = get_json(format!(
+                "{}/country/{}?c={}&f=json",
+                base_url,
+                encode(&country),
+                client_key
+            ))?;
+            let row = find_by_url(rows, &path).ok_or_else(not_found)?;
+            let category = pick_str(&row, &["Category"]).ok_or_else(not_found)?;
+            (TeSymbol::Indicator { country, category }, row)
+        }
+        Dataset::Market => {
+            let last = path.rsplit('/').next().unwrap_or("");
+            let row = if last.contains(':') {
+                let rows: Vec<Map<String, Value>> = get_json(format!(
+                    "{}/markets/symbol/{}?c={}&f=json",
+                    base_url,
+                    encode(last),
+                    client_key
+                ))?;
+                rows.into_iter().next()
+            } else {
+                let list = if path.starts_with("/crypto") {
+                    "crypto"
+                } else {
+                    "commodities"
+                };
+                let rows: Vec<Map<String, Value>> = get_json(format!(
+                    "{}/markets/{}?c={}&f=json",
+                    base_url, list, client_key
+                ))?;
+                find_by_url(rows, &path)
+            }
+            .ok_or_else(not_found)?;
+            let symbol = pick_str(&row, &["Symbol"]).ok_or_else(not_found)?;
+            (TeSymbol::Market(symbol), row)
+        }
+        Dataset::Fred => {
+            let rows: Vec<Map<String, Value>> = get_json(format!(
+                "{}/fred/snapshot/url?url={}&c={}",
+                base_url,
+                encode(&path),
+                client_key
+            ))?;
+            let row = rows.into_iter().next().ok_or_else(not_found)?;
+            let symbol = pick_str(&row, &["Symbol"]).ok_or_else(not_found)?;
+            (TeSymbol::Fred(symbol), row)
+        }
+        Dataset::WorldBank => {
+            let row: Map<String, Value> = match get_json::<Value>(format!(
+                "{}/worldBank/indicator?url={}&c={}",
+                base_url,
+                encode(&path),
+                client_key
+            ))? {
+                Value::Array(rows) => rows.into_iter().find_map(into_object),
+                other => into_object(other),
+            }
+            .ok_or_else(not_found)?;
+            let symbol =
+                pick_str(&row, &["Symbol", "HistoricalDataSymbol"]).ok_or_else(not_found)?;
+            (TeSymbol::WorldBank(symbol), row)
+        }
+        Dataset::Eurostat => {
+            let rows: Vec<Map<String, Value>> = get_json(format!(
+                "{}/eurostat/country/{}?c={}",
+                base_url,
+                encode(&country),
+                client_key
+            ))?;
+            let row = find_by_url(rows, &path).ok_or_else(not_found)?;
+            let id = pick_str(&row, &["ID", "Symbol"]).ok_or_else(not_found)?;
+            (TeSymbol::Eurostat(id), row)
+        }
+    };
+
+    let history = match &symbol {
+        TeSymbol::Indicator { country, category } => TimeSeries::from_historical(
+            &get_historical_country_indicator(base_url, client_key, country, category)?,
+        )?,
+        TeSymbol::Market(symbol) => {
+            TimeSeries::from_market_bars(&get_market_historical(base_url, client_key, symbol)?)?
+        }
+        TeSymbol::Fred(symbol) => {
+            let rows = get_fred_historical(base_url, client_key, &[symbol], None, None)?;
+            let points = rows
+                .iter()
+                .filter_map(|r| Some((parse_date(&r.date)?, r.value?)))
+                .collect();
+            series_from_points(symbol, &snapshot, points)
+        }
+        TeSymbol::WorldBank(symbol) => {
+            let rows = get_wb_historical(base_url, client_key, &symbol.parse::<WbSymbol>()?)?;
+            let points = rows
+                .iter()
+                .filter_map(|r| Some((parse_date(&r.date)?, r.value?)))
+                .collect();
+            series_from_points(symbol, &snapshot, points)
+        }
+        TeSymbol::Eurostat(id) => {
+            let rows = get_eurostat_historical(base_url, client_key, &[id], None, None)?;
+            let points = rows
+                .iter()
+                .filter_map(|r| Some((parse_date(&r.date)?, r.value?)))
+                .collect();
+            series_from_points(id, &snapshot, points)
+        }
+    };
+
+    Ok(Resolved {
+        name: pick_str(&snapshot, &["Title", "Name", "Category"]),
+        latest_value: pick_f64(&snapshot, &["LatestValue", "Last", "Value"]),
+        latest_date: pick_str(&snapshot, &["LatestValueDate", "Date"]),
+        symbol,
+        snapshot,
+        history,
+    })
+}
+
+fn find_by_url(rows: Vec<Map<String, Value>>, path: &str) -> Option<Map<String, Value>> {
+    rows.into_iter().find(|row| {
+        pick_str(row, &["URL"]).is_some_and(|u| u.trim_end_matches('/').eq_ignore_ascii_case(path))
+    })
+}
+
+fn into_object(value: Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+/// First of `keys` present in `row`, compared case-insensitively since the
+/// datasets disagree on casing.
+fn pick<'a>(row: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| {
+        row.iter()
+            .find(|(k, v)| k.eq_ignore_ascii_case(key) && !v.is_null())
+            .map(|(_, v)| v)
+    })
+}
+
+fn pick_str(row: &Map<String, Value>, keys: &[&str]) -> Option<String> {
+    match pick(row, keys)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn pick_f64(row: &Map<String, Value>, keys: &[&str]) -> Option<f64> {
+    match pick(row, keys)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Uses the snapshot's declared frequency, falling back to the spacing of
+/// the observations.
+fn series_from_points(
+    name: &str,
+    snapshot: &Map<String, Value>,
+    points: Vec<(chrono::NaiveDate, f64)>,
+) -> TimeSeries {
+    let mut dates: Vec<_> = points.iter().map(|p| p.0).collect();
+    dates.sort();
+    let frequency = pick_str(snapshot, &["Frequency"])
+        .and_then(|f| f.parse().ok())
+        .or_else(|| Frequency::infer(&dates))
+        .unwrap_or(Frequency::Yearly);
+    TimeSeries::new(name, frequency, points)
+}
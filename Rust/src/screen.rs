@@ -0,0 +1,545 @@
+//! Fundamentals screener over `/financials/companies` and
+//! `/financials/symbol/...`.
+//!
+//! Each company becomes a set of named numeric fields: `market_cap`, every
+//! latest line item under its slug with `-` written as `_` (`net_income`),
+//! and the level ratios of `financials::Ratio` computed from those items
+//! (`roe`, `debt_to_equity`, ...). Growth ratios need history and are not
+//! available here; use `financials::get_financial_ratios` for them.
+//!
+//! A filter is a small expression such as
+//! `roe > 0.15 and (debt_to_equity < 1 or not net_margin <= 0)`: comparisons
+//! of fields and numbers joined by `and`, `or` and `not`. A comparison with
+//! a field the company lacks is false.
+//!
+//! With a `Store` attached, company lists and line items are read from it
+//! while younger than the cache age and refetched otherwise.
+
+use crate::financials::{
+    get_financials_companies, get_financials_companies_country, get_financials_symbol, ratios,
+    Company, FinancialItem, Ratio,
+};
+use crate::storage::Store;
+use crate::timeseries::{parse_date, Frequency, TimeSeries};
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Companies per `/financials/symbol` request.
+const SYMBOL_BATCH: usize = 20;
+
+const LEVEL_RATIOS: [Ratio; 7] = [
+    Ratio::GrossMargin,
+    Ratio::OperatingMargin,
+    Ratio::NetMargin,
+    Ratio::DebtToEquity,
+    Ratio::DebtToAssets,
+    Ratio::Roe,
+    Ratio::Roa,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn apply(&self, a: f64, b: f64) -> bool {
+        match self {
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Field(String),
+    Number(f64),
+}
+
+impl Operand {
+    fn value(&self, fields: &BTreeMap<String, f64>) -> Option<f64> {
+        match self {
+            Operand::Field(name) => fields.get(name).copied(),
+            Operand::Number(n) => Some(*n),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Field(name) => f.write_str(name),
+            Operand::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// A parsed screen filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Compare(Operand, CmpOp, Operand),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    pub fn eval(&self, fields: &BTreeMap<String, f64>) -> bool {
+        match self {
+            Expr::Compare(a, op, b) => match (a.value(fields), b.value(fields)) {
+                (Some(a), Some(b)) => op.apply(a, b),
+                _ => false,
+            },
+            Expr::And(a, b) => a.eval(fields) && b.eval(fields),
+            Expr::Or(a, b) => a.eval(fields) || b.eval(fields),
+            Expr::Not(e) => !e.eval(fields),
+        }
+    }
+
+    /// Field names the expression reads, in order of appearance.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_fields(&mut out);
+        out
+    }
+
+    fn collect_fields<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Compare(a, _, b) => {
+                for operand in [a, b] {
+                    if let Operand::Field(name) = operand {
+                        if !out.contains(&name.as_str()) {
+                            out.push(name);
+                        }
+                    }
+                }
+            }
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                a.collect_fields(out);
+                b.collect_fields(out);
+            }
+            Expr::Not(e) => e.collect_fields(out),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Compare(a, op, b) => write!(f, "{} {} {}", a, op.as_str(), b),
+            Expr::And(a, b) => write!(f, "({} and {})", a, b),
+            Expr::Or(a, b) => write!(f, "({} or {})", a, b),
+            Expr::Not(e) => write!(f, "not {}", e),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = lex(s)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(t) => Err(format!("unexpected {} in filter", t)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Op(CmpOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "'{}'", s),
+            Token::Number(n) => write!(f, "'{}'", n),
+            Token::Op(op) => write!(f, "'{}'", op.as_str()),
+            Token::And => f.write_str("'and'"),
+            Token::Or => f.write_str("'or'"),
+            Token::Not => f.write_str("'not'"),
+            Token::Open => f.write_str("'('"),
+            Token::Close => f.write_str("')'"),
+        }
+    }
+}
+
+fn lex(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let (token, len) = match (c, next) {
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('<', Some('=')) => (Token::Op(CmpOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(CmpOp::Ge), 2),
+            ('=', Some('=')) => (Token::Op(CmpOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CmpOp::Ne), 2),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('<', _) => (Token::Op(CmpOp::Lt), 1),
+            ('>', _) => (Token::Op(CmpOp::Gt), 1),
+            ('=', _) => (Token::Op(CmpOp::Eq), 1),
+            ('!', _) => (Token::Not, 1),
+            _ if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let start = i;
+                let mut end = i + 1;
+                while end < chars.len()
+                    && (chars[end].is_ascii_alphanumeric()
+                        || chars[end] == '.'
+                        || (matches!(chars[end], '-' | '+') && matches!(chars[end - 1], 'e' | 'E')))
+                {
+                    end += 1;
+                }
+                let text: String = chars[start..end].iter().collect();
+                let n = text
+                    .parse()
+                    .map_err(|_| format!("bad number in filter: {}", text))?;
+                (Token::Number(n), end - start)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                let mut end = i + 1;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                let word: String = chars[start..end].iter().collect();
+                let token = match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    lower => Token::Ident(lower.to_string()),
+                };
+                (token, end - start)
+            }
+            _ => return Err(format!("unexpected '{}' in filter", c)),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent: `or := and ("or" and)*`, `and := not ("and" not)*`,
+/// `not := "not" not | "(" or ")" | operand op operand`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.eat(&Token::Open) {
+            let expr = self.or()?;
+            if !self.eat(&Token::Close) {
+                return Err("missing ')' in filter".to_string());
+            }
+            return Ok(expr);
+        }
+        let a = self.operand()?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(t) => return Err(format!("expected a comparison, found {}", t)),
+            None => return Err("filter ends before a comparison".to_string()),
+        };
+        let b = self.operand()?;
+        Ok(Expr::Compare(a, op, b))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(Operand::Field(name)),
+            Some(Token::Number(n)) => Ok(Operand::Number(n)),
+            Some(t) => Err(format!("expected a field or number, found {}", t)),
+            None => Err("filter ends early".to_string()),
+        }
+    }
+}
+
+/// One company that passed the screen, with every field it has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenRow {
+    pub company: Company,
+    pub fields: BTreeMap<String, f64>,
+}
+
+/// The screen fields of a company: market cap, latest line items and level
+/// ratios computed from items reported for the same period.
+pub fn company_fields(company: &Company, items: &[FinancialItem]) -> BTreeMap<String, f64> {
+    let mut fields = BTreeMap::new();
+    let mut series = BTreeMap::new();
+    for item in items {
+        let (Some(slug), Some(value)) = (item.item(), item.value) else {
+            continue;
+        };
+        fields.insert(slug.replace('-', "_"), value);
+        if let Some(date) = item.date.as_deref().and_then(parse_date) {
+            let s = TimeSeries::new(&slug, Frequency::Quarterly, vec![(date, value)]);
+            series.insert(slug, s);
+        }
+    }
+    if let Some(cap) = company.market_cap_usd {
+        fields.insert("market_cap".to_string(), cap);
+    } else if let Some(cap) = fields.get("market_capitalization").copied() {
+        fields.insert("market_cap".to_string(), cap);
+    }
+    let aligned = ratios(&series, &LEVEL_RATIOS);
+    for (name, column) in aligned.columns {
+        if let Some(v) = column.into_iter().flatten().last() {
+            fields.insert(name, v);
+        }
+    }
+    fields
+}
+
+/// A fundamentals screen, built up and then `run`.
+pub struct Screener {
+    base_url: String,
+    client_key: String,
+    countries: Vec<String>,
+    filter: Option<Expr>,
+    sort: Option<(String, bool)>,
+    limit: Option<usize>,
+    store: Option<Store>,
+    max_age: Duration,
+}
+
+impl Screener {
+    pub fn new(base_url: &str, client_key: &str) -> Screener {
+        Screener {
+            base_url: base_url.to_string(),
+            client_key: client_key.to_string(),
+            countries: Vec::new(),
+            filter: None,
+            sort: None,
+            limit: None,
+            store: None,
+            max_age: Duration::hours(24),
+        }
+    }
+
+    /// Screens the companies of `country`; without any, every company.
+    pub fn country(mut self, country: &str) -> Screener {
+        self.countries.push(country.to_string());
+        self
+    }
+
+    pub fn filter(mut self, filter: Expr) -> Screener {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Orders rows by `field`, largest first when `descending`; rows
+    /// without the field go last.
+    pub fn sort(mut self, field: &str, descending: bool) -> Screener {
+        self.sort = Some((field.to_lowercase(), descending));
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Screener {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Reads and writes pulls through `store`, refetching those older than
+    /// `max_age` (a day by default).
+    pub fn cache(mut self, store: Store, max_age: Duration) -> Screener {
+        self.store = Some(store);
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn run(&mut self) -> Result<Vec<ScreenRow>, Box<dyn Error + Send + Sync>> {
+        let companies = self.companies()?;
+        let symbols: Vec<&str> = companies.iter().map(|c| c.symbol.as_str()).collect();
+        let mut items = self.items(&symbols)?;
+        let mut rows: Vec<ScreenRow> = companies
+            .iter()
+            .map(|company| {
+                let own = items
+                    .remove(&company.symbol.to_lowercase())
+                    .unwrap_or_default();
+                ScreenRow {
+                    fields: company_fields(company, &own),
+                    company: company.clone(),
+                }
+            })
+            .filter(|row| self.filter.as_ref().is_none_or(|f| f.eval(&row.fields)))
+            .collect();
+        if let Some((field, descending)) = &self.sort {
+            rows.sort_by(|a, b| match (a.fields.get(field), b.fields.get(field)) {
+                (Some(x), Some(y)) if *descending => y.total_cmp(x),
+                (Some(x), Some(y)) => x.total_cmp(y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        }
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+        Ok(rows)
+    }
+
+    fn companies(&mut self) -> Result<Vec<Company>, Box<dyn Error + Send + Sync>> {
+        let (base_url, client_key) = (self.base_url.clone(), self.client_key.clone());
+        if self.countries.is_empty() {
+            let source = "/financials/companies";
+            if self.fresh(source)? {
+                return self.store.as_ref().unwrap().companies(None);
+            }
+            let companies = get_financials_companies(base_url, client_key)?;
+            if let Some(store) = self.store.as_mut() {
+                store.upsert_companies(source, &companies)?;
+            }
+            return Ok(companies);
+        }
+        let mut out = Vec::new();
+        for country in self.countries.clone() {
+            let source = format!("/financials/companies?country={}", country.to_lowercase());
+            if self.fresh(&source)? {
+                out.extend(self.store.as_ref().unwrap().companies(Some(&country))?);
+                continue;
+            }
+            let companies = get_financials_companies_country(
+                base_url.clone(),
+                client_key.clone(),
+                &[&country],
+            )?;
+            if let Some(store) = self.store.as_mut() {
+                store.upsert_companies(&source, &companies)?;
+            }
+            out.extend(companies);
+        }
+        Ok(out)
+    }
+
+    /// Latest line items per lowercased company symbol.
+    fn items(
+        &mut self,
+        symbols: &[&str],
+    ) -> Result<BTreeMap<String, Vec<FinancialItem>>, Box<dyn Error + Send + Sync>> {
+        let mut out: BTreeMap<String, Vec<FinancialItem>> = BTreeMap::new();
+        let mut stale = Vec::new();
+        for &symbol in symbols {
+            let source = format!("/financials/symbol/{}", symbol.to_lowercase());
+            if self.fresh(&source)? {
+                let items = self.store.as_ref().unwrap().financial_items(symbol)?;
+                out.insert(symbol.to_lowercase(), items);
+            } else {
+                stale.push(symbol);
+            }
+        }
+        for batch in stale.chunks(SYMBOL_BATCH) {
+            let fetched =
+                get_financials_symbol(self.base_url.clone(), self.client_key.clone(), batch)?;
+            let mut by_symbol: BTreeMap<String, Vec<FinancialItem>> = BTreeMap::new();
+            for item in fetched {
+                by_symbol
+                    .entry(item.symbol.to_lowercase())
+                    .or_default()
+                    .push(item);
+            }
+            if let Some(store) = self.store.as_mut() {
+                for symbol in batch {
+                    let symbol = symbol.to_lowercase();
+                    let items = by_symbol.get(&symbol).map(Vec::as_slice).unwrap_or(&[]);
+                    let source = format!("/financials/symbol/{}", symbol);
+                    store.upsert_financial_items(&source, items)?;
+                }
+            }
+            out.extend(by_symbol);
+        }
+        Ok(out)
+    }
+
+    /// Whether the store holds a pull of `source` younger than the cache
+    /// age.
+    fn fresh(&self, source: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        Ok(match store.last_updated(source)? {
+            Some(ts) => DateTime::parse_from_rfc3339(&ts)
+                .is_ok_and(|t| Utc::now() - t.with_timezone(&Utc) < self.max_age),
+            None => false,
+        })
+    }
+}
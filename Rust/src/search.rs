@@ -0,0 +1,323 @@
+//! Local full-text search over stored news and calendar events.
+//!
+//! `SearchIndex` is an in-memory inverted index: text is lowercased, split on
+//! anything that is not a letter or digit, stripped of English stop words
+//! and reduced with the Porter2 stemmer, so "hikes" finds "hiking" and
+//! "hike". Matches are ranked with Okapi BM25 and can be narrowed by kind,
+//! country, category and date; the facet counts of a result set tell which
+//! narrowing is worth applying next.
+
+use crate::calendar::CalendarEvent;
+use crate::news::NewsItem;
+use crate::storage::Store;
+use crate::timeseries::parse_date;
+use chrono::NaiveDate;
+use rust_stemmers::{Algorithm, Stemmer};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+
+/// BM25 term-frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 length normalisation.
+const B: f64 = 0.75;
+/// Title terms count this many times, so a headline match outranks a
+/// passing mention in the body.
+const TITLE_WEIGHT: u32 = 2;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "have", "in", "is",
+    "it", "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DocKind {
+    News,
+    Calendar,
+}
+
+impl DocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocKind::News => "news",
+            DocKind::Calendar => "calendar",
+        }
+    }
+}
+
+/// The searchable text and facets of one news item or calendar event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub kind: DocKind,
+    /// News id or calendar id.
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub country: Option<String>,
+    pub category: Option<String>,
+    pub date: Option<NaiveDate>,
+}
+
+impl From<&NewsItem> for Document {
+    fn from(item: &NewsItem) -> Document {
+        Document {
+            kind: DocKind::News,
+            id: item.id.clone(),
+            title: item.title.clone(),
+            body: item.description.clone().unwrap_or_default(),
+            country: item.country.clone(),
+            category: item.category.clone(),
+            date: parse_date(&item.date),
+        }
+    }
+}
+
+impl From<&CalendarEvent> for Document {
+    fn from(event: &CalendarEvent) -> Document {
+        Document {
+            kind: DocKind::Calendar,
+            id: event.calendar_id.clone(),
+            title: event.event.clone(),
+            body: [Some(&event.category), event.reference.as_ref()]
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" "),
+            country: Some(event.country.clone()),
+            category: Some(event.category.clone()),
+            date: parse_date(&event.date),
+        }
+    }
+}
+
+/// Filters applied on top of the text match; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub kinds: Vec<DocKind>,
+    pub countries: Vec<String>,
+    pub categories: Vec<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Maximum hits returned; `None` returns all.
+    pub limit: Option<usize>,
+}
+
+impl SearchQuery {
+    pub fn new(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            ..SearchQuery::default()
+        }
+    }
+
+    fn admits(&self, doc: &Document) -> bool {
+        let in_list = |list: &[String], value: &Option<String>| {
+            list.is_empty()
+                || value
+                    .as_deref()
+                    .is_some_and(|v| list.iter().any(|l| l.eq_ignore_ascii_case(v)))
+        };
+        if !self.kinds.is_empty() && !self.kinds.contains(&doc.kind) {
+            return false;
+        }
+        if !in_list(&self.countries, &doc.country) || !in_list(&self.categories, &doc.category) {
+            return false;
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let Some(date) = doc.date else {
+                return false;
+            };
+            if self.from.is_some_and(|f| date < f) || self.to.is_some_and(|t| date > t) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit<'a> {
+    pub document: &'a Document,
+    pub score: f64,
+}
+
+/// Counts of hits per facet value, largest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Facets {
+    pub kinds: Vec<(DocKind, usize)>,
+    pub countries: Vec<(String, usize)>,
+    pub categories: Vec<(String, usize)>,
+    /// Hits per month, as `yyyy-mm`, in date order.
+    pub months: Vec<(String, usize)>,
+}
+
+pub struct SearchIndex {
+    stemmer: Stemmer,
+    docs: Vec<Document>,
+    lengths: Vec<u32>,
+    total_length: u64,
+    postings: HashMap<String, Vec<(usize, u32)>>,
+    ids: HashSet<(DocKind, String)>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> SearchIndex {
+        SearchIndex::new()
+    }
+}
+
+impl SearchIndex {
+    pub fn new() -> SearchIndex {
+        SearchIndex {
+            stemmer: Stemmer::create(Algorithm::English),
+            docs: Vec::new(),
+            lengths: Vec::new(),
+            total_length: 0,
+            postings: HashMap::new(),
+            ids: HashSet::new(),
+        }
+    }
+
+    /// Indexes every news item and calendar event in the store.
+    pub fn from_store(store: &Store) -> Result<SearchIndex, Box<dyn Error + Send + Sync>> {
+        let mut index = SearchIndex::new();
+        for item in store.news(None, None, None)? {
+            index.add(Document::from(&item));
+        }
+        for event in store.calendar_events(None, None, None)? {
+            index.add(Document::from(&event));
+        }
+        Ok(index)
+    }
+
+    /// Adds a document; one already indexed under the same kind and id is
+    /// kept as it is.
+    pub fn add(&mut self, doc: Document) -> bool {
+        if !self.ids.insert((doc.kind, doc.id.clone())) {
+            return false;
+        }
+        let n = self.docs.len();
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for term in self.tokenize(&doc.title) {
+            *counts.entry(term).or_insert(0) += TITLE_WEIGHT;
+        }
+        for term in self.tokenize(&doc.body) {
+            *counts.entry(term).or_insert(0) += 1;
+        }
+        let length: u32 = counts.values().sum();
+        for (term, tf) in counts {
+            self.postings.entry(term).or_default().push((n, tf));
+        }
+        self.lengths.push(length);
+        self.total_length += length as u64;
+        self.docs.push(doc);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Lowercased, stop-word-free, stemmed terms of `text`.
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty() && !STOP_WORDS.contains(w))
+            .map(|w| self.stemmer.stem(w).into_owned())
+            .collect()
+    }
+
+    /// Documents matching any query term and the filters, best first. An
+    /// empty query text lists every admitted document, newest first.
+    pub fn search(&self, query: &SearchQuery) -> Vec<Hit<'_>> {
+        let mut terms = self.tokenize(&query.text);
+        terms.sort();
+        terms.dedup();
+        let mut hits: Vec<Hit> = if terms.is_empty() {
+            let mut all: Vec<Hit> = self
+                .docs
+                .iter()
+                .filter(|d| query.admits(d))
+                .map(|document| Hit {
+                    document,
+                    score: 0.0,
+                })
+                .collect();
+            all.sort_by_key(|h| Reverse(h.document.date));
+            all
+        } else {
+            let n = self.docs.len() as f64;
+            let avg = self.total_length as f64 / n.max(1.0);
+            let mut scores: HashMap<usize, f64> = HashMap::new();
+            for term in &terms {
+                let Some(postings) = self.postings.get(term) else {
+                    continue;
+                };
+                let df = postings.len() as f64;
+                let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                for &(doc, tf) in postings {
+                    let tf = tf as f64;
+                    let norm = 1.0 - B + B * self.lengths[doc] as f64 / avg;
+                    *scores.entry(doc).or_insert(0.0) += idf * tf * (K1 + 1.0) / (tf + K1 * norm);
+                }
+            }
+            let mut hits: Vec<Hit> = scores
+                .into_iter()
+                .filter(|(doc, _)| query.admits(&self.docs[*doc]))
+                .map(|(doc, score)| Hit {
+                    document: &self.docs[doc],
+                    score,
+                })
+                .collect();
+            hits.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then(b.document.date.cmp(&a.document.date))
+            });
+            hits
+        };
+        if let Some(limit) = query.limit {
+            hits.truncate(limit);
+        }
+        hits
+    }
+
+    /// Facet counts over `hits`.
+    pub fn facets(hits: &[Hit<'_>]) -> Facets {
+        let mut kinds: BTreeMap<DocKind, usize> = BTreeMap::new();
+        let mut countries: BTreeMap<String, usize> = BTreeMap::new();
+        let mut categories: BTreeMap<String, usize> = BTreeMap::new();
+        let mut months: BTreeMap<String, usize> = BTreeMap::new();
+        for hit in hits {
+            let doc = hit.document;
+            *kinds.entry(doc.kind).or_insert(0) += 1;
+            if let Some(c) = &doc.country {
+                *countries.entry(c.clone()).or_insert(0) += 1;
+            }
+            if let Some(c) = &doc.category {
+                *categories.entry(c.clone()).or_insert(0) += 1;
+            }
+            if let Some(d) = doc.date {
+                *months.entry(d.format("%Y-%m").to_string()).or_insert(0) += 1;
+            }
+        }
+        Facets {
+            kinds: by_count(kinds),
+            countries: by_count(countries),
+            categories: by_count(categories),
+            months: months.into_iter().collect(),
+        }
+    }
+}
+
+fn by_count<K: Ord>(counts: BTreeMap<K, usize>) -> Vec<(K, usize)> {
+    let mut out: Vec<(K, usize)> = counts.into_iter().collect();
+    out.sort_by_key(|c| Reverse(c.1));
+    out
+}
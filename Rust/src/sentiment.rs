@@ -0,0 +1,289 @@
+//! Dictionary sentiment for news items, in the manner of Loughran and
+//! McDonald (2011).
+//!
+//! Words are matched whole and unstemmed against positive, negative and
+//! uncertainty lists, so the lists carry their own inflections. A positive
+//! word with a negator among the three words before it in the same clause
+//! ("did not improve") counts as negative, and a negated negative word
+//! counts as neither. The built-in lists are a finance-specific core;
+//! `Lexicon::load_master` reads the full Loughran–McDonald master dictionary
+//! CSV.
+
+use crate::news::NewsItem;
+use crate::timeseries::{parse_date, Frequency, TimeSeries};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Words before a tone word searched for a negator.
+const NEGATION_WINDOW: usize = 3;
+
+#[rustfmt::skip]
+const NEGATORS: &[&str] = &[
+    "no", "not", "none", "neither", "never", "nobody", "nor", "without", "cannot", "isn't",
+    "aren't", "wasn't", "weren't", "don't", "doesn't", "didn't", "won't", "wouldn't", "hasn't",
+    "haven't", "hadn't", "can't", "couldn't", "shouldn't",
+];
+
+#[rustfmt::skip]
+const POSITIVE: &[&str] = &[
+    "achieve", "achieved", "achievement", "advance", "advanced", "advances", "advancing",
+    "attractive", "beat", "beats", "benefit", "benefited", "benefits", "best", "better", "boom",
+    "booming", "boost", "boosted", "boosting", "boosts", "encouraging", "enhance", "enhanced",
+    "expand", "expanded", "expanding", "expansion", "favorable", "gain", "gained", "gaining",
+    "gains", "good", "great", "greater", "highest", "improve", "improved", "improvement",
+    "improvements", "improves", "improving", "opportunities", "opportunity", "optimism",
+    "optimistic", "outperform", "outperformed", "positive", "profitable", "progress",
+    "prosperity", "rally", "rallied", "rebound", "rebounded", "record", "recover", "recovered",
+    "recovering", "recovery", "resilient", "rise", "robust", "solid", "stabilize", "stabilized",
+    "stable", "strength", "strengthen", "strengthened", "strong", "stronger", "strongest",
+    "succeed", "success", "successful", "surge", "surged", "surpassed", "upturn",
+];
+
+#[rustfmt::skip]
+const NEGATIVE: &[&str] = &[
+    "adverse", "bankrupt", "bankruptcy", "collapse", "collapsed", "concern", "concerns",
+    "contraction", "crisis", "critical", "decline", "declined", "declines", "declining",
+    "decrease", "decreased", "default", "defaults", "deficit", "deficits", "deteriorate",
+    "deteriorated", "deteriorating", "deterioration", "disappoint", "disappointed",
+    "disappointing", "downgrade", "downgraded", "downturn", "drop", "dropped", "drops", "fall",
+    "fallen", "falling", "falls", "fell", "fail", "failed", "failure", "fear", "fears", "hurt",
+    "layoffs", "lose", "loss", "losses", "lost", "lowest", "negative", "plunge", "plunged",
+    "recession", "recessionary", "shortfall", "shrank", "shrink", "shrinking", "slowdown",
+    "slump", "slumped", "slowed", "slowing", "stagnant", "stagnation", "tumble", "tumbled",
+    "turmoil", "weak", "weaken", "weakened", "weakening", "weaker", "weakest", "weakness",
+    "worse", "worsen", "worsened", "worsening", "worst",
+];
+
+#[rustfmt::skip]
+const UNCERTAINTY: &[&str] = &[
+    "almost", "anticipate", "anticipated", "appear", "appears", "approximately", "assume",
+    "assumed", "believe", "believes", "could", "depend", "depends", "doubt", "doubts",
+    "exposure", "fluctuate", "fluctuation", "fluctuations", "indefinite", "likely", "may",
+    "maybe", "might", "nearly", "pending", "perhaps", "possible", "possibly", "predict",
+    "preliminary", "probable", "probably", "risk", "risky", "roughly", "seems", "sometimes",
+    "speculative", "suggest", "suggests", "tentative", "uncertain", "uncertainties",
+    "uncertainty", "unclear", "unknown", "unpredictable", "unsettled", "variable", "volatile",
+    "volatility",
+];
+
+/// Positive, negative and uncertainty word lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexicon {
+    positive: HashSet<String>,
+    negative: HashSet<String>,
+    uncertainty: HashSet<String>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Lexicon {
+        Lexicon::new(POSITIVE, NEGATIVE, UNCERTAINTY)
+    }
+}
+
+impl Lexicon {
+    pub fn new(positive: &[&str], negative: &[&str], uncertainty: &[&str]) -> Lexicon {
+        let set = |words: &[&str]| words.iter().map(|w| w.to_lowercase()).collect();
+        Lexicon {
+            positive: set(positive),
+            negative: set(negative),
+            uncertainty: set(uncertainty),
+        }
+    }
+
+    /// Reads the Loughran–McDonald master dictionary CSV: a word belongs to
+    /// a list when its `Positive`, `Negative` or `Uncertainty` column is
+    /// positive. The columns hold the year the word was added; a negative
+    /// year means it was later removed from the list.
+    pub fn load_master<P: AsRef<Path>>(path: P) -> Result<Lexicon, Box<dyn Error + Send + Sync>> {
+        let text = fs::read_to_string(path)?;
+        let mut lines = text.lines();
+        let header: Vec<String> = lines
+            .next()
+            .ok_or("empty dictionary")?
+            .split(',')
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| format!("dictionary has no {} column", name))
+        };
+        let (word, pos, neg, unc) = (
+            column("word")?,
+            column("positive")?,
+            column("negative")?,
+            column("uncertainty")?,
+        );
+        let mut lexicon = Lexicon::new(&[], &[], &[]);
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').collect();
+            let Some(w) = fields.get(word).map(|w| w.trim().to_lowercase()) else {
+                continue;
+            };
+            let flagged = |i: usize| {
+                fields
+                    .get(i)
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+                    .is_some_and(|v| v > 0.0)
+            };
+            if flagged(pos) {
+                lexicon.positive.insert(w.clone());
+            }
+            if flagged(neg) {
+                lexicon.negative.insert(w.clone());
+            }
+            if flagged(unc) {
+                lexicon.uncertainty.insert(w);
+            }
+        }
+        Ok(lexicon)
+    }
+
+    /// Counts tone words in `text`.
+    pub fn score(&self, text: &str) -> Sentiment {
+        let mut s = Sentiment::default();
+        for clause in clauses(text) {
+            let words = tokenize(clause);
+            s.words += words.len();
+            for (i, word) in words.iter().enumerate() {
+                let negated = words[i.saturating_sub(NEGATION_WINDOW)..i]
+                    .iter()
+                    .any(|w| NEGATORS.contains(&w.as_str()));
+                if self.positive.contains(word) {
+                    if negated {
+                        s.negative += 1;
+                    } else {
+                        s.positive += 1;
+                    }
+                } else if self.negative.contains(word) && !negated {
+                    s.negative += 1;
+                }
+                if self.uncertainty.contains(word) {
+                    s.uncertainty += 1;
+                }
+            }
+        }
+        s
+    }
+
+    /// Scores the title and description of `item`.
+    pub fn score_news(&self, item: &NewsItem) -> Sentiment {
+        match &item.description {
+            Some(d) => self.score(&format!("{}. {}", item.title, d)),
+            None => self.score(&item.title),
+        }
+    }
+}
+
+/// Tone word counts of one text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sentiment {
+    pub positive: usize,
+    pub negative: usize,
+    pub uncertainty: usize,
+    /// All words in the text.
+    pub words: usize,
+}
+
+impl Sentiment {
+    /// `(positive - negative) / (positive + negative)`, in `[-1, 1]`; `None`
+    /// when the text has no tone words.
+    pub fn polarity(&self) -> Option<f64> {
+        let tone = self.positive + self.negative;
+        (tone > 0).then(|| (self.positive as f64 - self.negative as f64) / tone as f64)
+    }
+
+    /// Net tone per word.
+    pub fn net_tone(&self) -> Option<f64> {
+        (self.words > 0).then(|| (self.positive as f64 - self.negative as f64) / self.words as f64)
+    }
+
+    /// Share of words that express uncertainty.
+    pub fn uncertainty_share(&self) -> Option<f64> {
+        (self.words > 0).then(|| self.uncertainty as f64 / self.words as f64)
+    }
+}
+
+/// Mean polarity of each country's items per day, one daily series per
+/// country named after it. Items without a country, a readable date or any
+/// tone words are left out.
+pub fn daily_sentiment(lexicon: &Lexicon, items: &[NewsItem]) -> BTreeMap<String, TimeSeries> {
+    let mut sums: BTreeMap<String, BTreeMap<NaiveDate, (f64, usize)>> = BTreeMap::new();
+    for item in items {
+        let (Some(country), Some(date)) = (&item.country, parse_date(&item.date)) else {
+            continue;
+        };
+        let Some(polarity) = lexicon.score_news(item).polarity() else {
+            continue;
+        };
+        let day = sums
+            .entry(country.clone())
+            .or_default()
+            .entry(date)
+            .or_insert((0.0, 0));
+        day.0 += polarity;
+        day.1 += 1;
+    }
+    sums.into_iter()
+        .map(|(country, days)| {
+            let points = days
+                .into_iter()
+                .map(|(date, (sum, n))| (date, sum / n as f64))
+                .collect();
+            let series = TimeSeries::new(&country, Frequency::Daily, points);
+            (country, series)
+        })
+        .collect()
+}
+
+/// Splits `text` at clause punctuation. A full stop or comma only ends a
+/// clause when whitespace or the end of the text follows, so "2.5%" and
+/// "1,000" stay whole.
+fn clauses(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let ends = match c {
+            '.' | ',' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            ';' | ':' | '!' | '?' => true,
+            _ => false,
+        };
+        if ends {
+            out.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    out.push(&text[start..]);
+    out
+}
+
+/// Lowercased words, keeping apostrophes so contracted negators survive and
+/// numbers such as "1,000" or "2.5" whole so they count as one word.
+fn tokenize(text: &str) -> Vec<String> {
+    let text = text.to_lowercase().replace('\u{2019}', "'");
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let separator_in_number = matches!(c, '.' | ',')
+            && i > 0
+            && chars[i - 1].is_ascii_digit()
+            && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit());
+        if c.is_alphanumeric() || c == '\'' || separator_in_number {
+            word.push(c);
+        } else if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+    words.push(word);
+    words
+        .into_iter()
+        .map(|w| w.trim_matches('\'').to_string())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
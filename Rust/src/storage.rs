@@ -0,0 +1,739 @@
+//! Local SQLite store for data fetched from the Trading Economics API.
+//!
+//! Every table is keyed by its natural identifier (symbol/country/date) so
+//! re-fetching the same endpoint upserts rows instead of duplicating them.
+//! Each row records the endpoint it came from and when it was written, and
+//! `fetch_log` keeps the last write time per endpoint.
+
+use crate::calendar::CalendarEvent;
+use crate::financials::{Company, FinancialItem};
+use crate::forecast::{ForecastKind, Horizon, VintageForecast};
+use crate::indicators::HistoricalData;
+use crate::markets::MarketBar;
+use crate::news::NewsItem;
+use crate::ratings::HistoricalRating;
+use crate::timeseries::parse_date;
+use chrono::Utc;
+use comtrade::ComtradeFlow;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::error::Error;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS indicator_history (
+    country TEXT NOT NULL COLLATE NOCASE,
+    category TEXT NOT NULL COLLATE NOCASE,
+    date TEXT NOT NULL,
+    value REAL,
+    frequency TEXT,
+    symbol TEXT,
+    api_last_update TEXT,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (country, category, date)
+);
+CREATE TABLE IF NOT EXISTS market_bars (
+    symbol TEXT NOT NULL COLLATE NOCASE,
+    date TEXT NOT NULL,
+    open REAL,
+    high REAL,
+    low REAL,
+    close REAL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (symbol, date)
+);
+CREATE TABLE IF NOT EXISTS calendar_events (
+    calendar_id TEXT NOT NULL PRIMARY KEY,
+    country TEXT NOT NULL COLLATE NOCASE,
+    date TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS calendar_events_country_date ON calendar_events (country, date);
+CREATE TABLE IF NOT EXISTS news_items (
+    id TEXT NOT NULL PRIMARY KEY,
+    country TEXT COLLATE NOCASE,
+    date TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS news_items_country_date ON news_items (country, date);
+CREATE TABLE IF NOT EXISTS ratings (
+    country TEXT NOT NULL COLLATE NOCASE,
+    agency TEXT NOT NULL COLLATE NOCASE,
+    date TEXT NOT NULL,
+    rating TEXT NOT NULL,
+    outlook TEXT,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (country, agency, date)
+);
+CREATE TABLE IF NOT EXISTS comtrade_flows (
+    symbol TEXT NOT NULL COLLATE NOCASE,
+    date TEXT NOT NULL,
+    value REAL,
+    country1 TEXT COLLATE NOCASE,
+    country2 TEXT COLLATE NOCASE,
+    flow_type TEXT,
+    category TEXT,
+    api_last_update TEXT,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (symbol, date)
+);
+CREATE TABLE IF NOT EXISTS forecast_vintages (
+    kind TEXT NOT NULL,
+    country TEXT NOT NULL COLLATE NOCASE,
+    series TEXT NOT NULL COLLATE NOCASE,
+    vintage TEXT NOT NULL,
+    horizon TEXT NOT NULL,
+    target_date TEXT NOT NULL,
+    value REAL NOT NULL,
+    base_value REAL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (kind, country, series, vintage, horizon)
+);
+CREATE TABLE IF NOT EXISTS financial_companies (
+    symbol TEXT NOT NULL PRIMARY KEY COLLATE NOCASE,
+    country TEXT COLLATE NOCASE,
+    payload TEXT NOT NULL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS financial_companies_country ON financial_companies (country);
+CREATE TABLE IF NOT EXISTS financial_items (
+    symbol TEXT NOT NULL COLLATE NOCASE,
+    item TEXT NOT NULL COLLATE NOCASE,
+    date TEXT,
+    value REAL,
+    payload TEXT NOT NULL,
+    source TEXT NOT NULL,
+    last_updated TEXT NOT NULL,
+    PRIMARY KEY (symbol, item)
+);
+CREATE TABLE IF NOT EXISTS fetch_log (
+    source TEXT NOT NULL PRIMARY KEY,
+    rows INTEGER NOT NULL,
+    last_updated TEXT NOT NULL
+);
+";
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens (or creates) the database at `path` and applies the schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store, Box<dyn Error + Send + Sync>> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    pub fn open_in_memory() -> Result<Store, Box<dyn Error + Send + Sync>> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Gives direct access to the connection for ad-hoc SQL from dashboards.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn upsert_indicator_history(
+        &mut self,
+        source: &str,
+        rows: &[HistoricalData],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO indicator_history
+                    (country, category, date, value, frequency, symbol, api_last_update, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (country, category, date) DO UPDATE SET
+                    value = excluded.value,
+                    frequency = excluded.frequency,
+                    symbol = excluded.symbol,
+                    api_last_update = excluded.api_last_update,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    row.country,
+                    row.category,
+                    row.date_time,
+                    row.value,
+                    row.frequency,
+                    row.historical_data_symbol,
+                    row.last_update,
+                    source,
+                    now,
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    pub fn indicator_history(
+        &self,
+        country: &str,
+        category: &str,
+    ) -> Result<Vec<HistoricalData>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT country, category, date, value, frequency, symbol, api_last_update
+             FROM indicator_history
+             WHERE country = ?1 AND category = ?2
+             ORDER BY date",
+        )?;
+        let rows = stmt
+            .query_map(params![country, category], |r| {
+                Ok(HistoricalData {
+                    country: r.get(0)?,
+                    category: r.get(1)?,
+                    date_time: r.get(2)?,
+                    value: r.get(3)?,
+                    frequency: r.get(4)?,
+                    historical_data_symbol: r.get(5)?,
+                    last_update: r.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn upsert_market_bars(
+        &mut self,
+        source: &str,
+        rows: &[MarketBar],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO market_bars (symbol, date, open, high, low, close, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (symbol, date) DO UPDATE SET
+                    open = excluded.open,
+                    high = excluded.high,
+                    low = excluded.low,
+                    close = excluded.close,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    row.symbol,
+                    iso_date(&row.date),
+                    row.open,
+                    row.high,
+                    row.low,
+                    row.close,
+                    source,
+                    now,
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    pub fn market_bars(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<MarketBar>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, date, open, high, low, close
+             FROM market_bars
+             WHERE symbol = ?1
+             ORDER BY date",
+        )?;
+        let rows = stmt
+            .query_map(params![symbol], |r| {
+                Ok(MarketBar {
+                    symbol: r.get(0)?,
+                    date: r.get(1)?,
+                    open: r.get(2)?,
+                    high: r.get(3)?,
+                    low: r.get(4)?,
+                    close: r.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn upsert_calendar_events(
+        &mut self,
+        source: &str,
+        rows: &[CalendarEvent],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO calendar_events (calendar_id, country, date, payload, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (calendar_id) DO UPDATE SET
+                    country = excluded.country,
+                    date = excluded.date,
+                    payload = excluded.payload,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                let payload = serde_json::to_string(row)?;
+                stmt.execute(params![
+                    row.calendar_id,
+                    row.country,
+                    row.date,
+                    payload,
+                    source,
+                    now
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Returns stored calendar events, optionally for one country, released
+    /// within `[from, to]` when given. Both bounds are `YYYY-MM-DD` dates and
+    /// `to` includes the whole day.
+    pub fn calendar_events(
+        &self,
+        country: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Vec<CalendarEvent>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT payload FROM calendar_events
+             WHERE (?1 IS NULL OR country = ?1)
+               AND (?2 IS NULL OR date >= ?2)
+               AND (?3 IS NULL OR substr(date, 1, 10) <= ?3)
+             ORDER BY date",
+        )?;
+        let payloads = stmt
+            .query_map(params![country, from, to], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut events = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            events.push(serde_json::from_str(&payload)?);
+        }
+        Ok(events)
+    }
+
+    pub fn upsert_news(
+        &mut self,
+        source: &str,
+        rows: &[NewsItem],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO news_items (id, country, date, payload, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (id) DO UPDATE SET
+                    country = excluded.country,
+                    date = excluded.date,
+                    payload = excluded.payload,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                let payload = serde_json::to_string(row)?;
+                stmt.execute(params![row.id, row.country, row.date, payload, source, now])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Returns stored news items, optionally for one country, published
+    /// within `[from, to]` when given. Both bounds are `YYYY-MM-DD` dates and
+    /// `to` includes the whole day.
+    pub fn news(
+        &self,
+        country: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Vec<NewsItem>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT payload FROM news_items
+             WHERE (?1 IS NULL OR country = ?1)
+               AND (?2 IS NULL OR date >= ?2)
+               AND (?3 IS NULL OR substr(date, 1, 10) <= ?3)
+             ORDER BY date",
+        )?;
+        let payloads = stmt
+            .query_map(params![country, from, to], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut items = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            items.push(serde_json::from_str(&payload)?);
+        }
+        Ok(items)
+    }
+
+    pub fn upsert_ratings(
+        &mut self,
+        source: &str,
+        rows: &[HistoricalRating],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO ratings (country, agency, date, rating, outlook, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (country, agency, date) DO UPDATE SET
+                    rating = excluded.rating,
+                    outlook = excluded.outlook,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    row.country,
+                    row.agency,
+                    iso_date(&row.date),
+                    row.rating,
+                    row.outlook,
+                    source,
+                    now,
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    pub fn ratings(
+        &self,
+        country: &str,
+    ) -> Result<Vec<HistoricalRating>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT country, date, agency, rating, outlook
+             FROM ratings
+             WHERE country = ?1
+             ORDER BY agency, date",
+        )?;
+        let rows = stmt
+            .query_map(params![country], |r| {
+                Ok(HistoricalRating {
+                    country: r.get(0)?,
+                    date: r.get(1)?,
+                    agency: r.get(2)?,
+                    rating: r.get(3)?,
+                    outlook: r.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn upsert_comtrade_flows(
+        &mut self,
+        source: &str,
+        rows: &[ComtradeFlow],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO comtrade_flows
+                    (symbol, date, value, country1, country2, flow_type, category, api_last_update, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (symbol, date) DO UPDATE SET
+                    value = excluded.value,
+                    country1 = COALESCE(excluded.country1, comtrade_flows.country1),
+                    country2 = COALESCE(excluded.country2, comtrade_flows.country2),
+                    flow_type = COALESCE(excluded.flow_type, comtrade_flows.flow_type),
+                    category = COALESCE(excluded.category, comtrade_flows.category),
+                    api_last_update = excluded.api_last_update,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    row.symbol,
+                    row.date,
+                    row.value,
+                    row.country1,
+                    row.country2,
+                    row.flow_type,
+                    row.category,
+                    row.last_update,
+                    source,
+                    now,
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    pub fn comtrade_flows(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<ComtradeFlow>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, date, value, country1, country2, flow_type, category, api_last_update
+             FROM comtrade_flows
+             WHERE symbol = ?1
+             ORDER BY date",
+        )?;
+        let rows = stmt
+            .query_map(params![symbol], |r| {
+                Ok(ComtradeFlow {
+                    symbol: r.get(0)?,
+                    date: r.get(1)?,
+                    value: r.get(2)?,
+                    country1: r.get(3)?,
+                    country2: r.get(4)?,
+                    flow_type: r.get(5)?,
+                    category: r.get(6)?,
+                    last_update: r.get(7)?,
+                    ..Default::default()
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Stores forecast projections; snapshotting the same vintage twice
+    /// overwrites it.
+    pub fn upsert_forecast_vintages(
+        &mut self,
+        source: &str,
+        rows: &[VintageForecast],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO forecast_vintages
+                    (kind, country, series, vintage, horizon, target_date, value, base_value, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (kind, country, series, vintage, horizon) DO UPDATE SET
+                    target_date = excluded.target_date,
+                    value = excluded.value,
+                    base_value = excluded.base_value,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    row.kind.as_str(),
+                    row.country,
+                    row.series,
+                    row.vintage.to_string(),
+                    row.horizon.label(),
+                    row.target_date.to_string(),
+                    row.value,
+                    row.base_value,
+                    source,
+                    now,
+                ])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Stored forecast vintages, optionally limited to one country and/or
+    /// series (indicator category or market symbol).
+    pub fn forecast_vintages(
+        &self,
+        country: Option<&str>,
+        series: Option<&str>,
+    ) -> Result<Vec<VintageForecast>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, country, series, vintage, horizon, target_date, value, base_value
+             FROM forecast_vintages
+             WHERE (?1 IS NULL OR country = ?1) AND (?2 IS NULL OR series = ?2)
+             ORDER BY kind, country, series, vintage, horizon",
+        )?;
+        let raw = stmt
+            .query_map(params![country, series], |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, String>(1)?,
+                    r.get::<_, String>(2)?,
+                    r.get::<_, String>(3)?,
+                    r.get::<_, String>(4)?,
+                    r.get::<_, String>(5)?,
+                    r.get::<_, f64>(6)?,
+                    r.get::<_, Option<f64>>(7)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut rows = Vec::with_capacity(raw.len());
+        for (kind, country, series, vintage, horizon, target_date, value, base_value) in raw {
+            rows.push(VintageForecast {
+                kind: kind.parse::<ForecastKind>()?,
+                country,
+                series,
+                vintage: parse_date(&vintage).ok_or_else(|| format!("bad vintage: {}", vintage))?,
+                horizon: Horizon::parse(&horizon)
+                    .ok_or_else(|| format!("bad horizon: {}", horizon))?,
+                target_date: parse_date(&target_date)
+                    .ok_or_else(|| format!("bad target date: {}", target_date))?,
+                value,
+                base_value,
+            });
+        }
+        Ok(rows)
+    }
+
+    pub fn upsert_companies(
+        &mut self,
+        source: &str,
+        rows: &[Company],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO financial_companies (symbol, country, payload, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (symbol) DO UPDATE SET
+                    country = excluded.country,
+                    payload = excluded.payload,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                let payload = serde_json::to_string(row)?;
+                stmt.execute(params![row.symbol, row.country, payload, source, now])?;
+            }
+        }
+        log_fetch(&tx, source, rows.len(), &now)?;
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// Returns stored companies, optionally for one country.
+    pub fn companies(
+        &self,
+        country: Option<&str>,
+    ) -> Result<Vec<Company>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT payload FROM financial_companies
+             WHERE (?1 IS NULL OR country = ?1)
+             ORDER BY symbol",
+        )?;
+        let payloads = stmt
+            .query_map(params![country], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut companies = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            companies.push(serde_json::from_str(&payload)?);
+        }
+        Ok(companies)
+    }
+
+    /// Stores the latest line items; an item without a name is skipped.
+    pub fn upsert_financial_items(
+        &mut self,
+        source: &str,
+        rows: &[FinancialItem],
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        let mut n = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO financial_items (symbol, item, date, value, payload, source, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (symbol, item) DO UPDATE SET
+                    date = excluded.date,
+                    value = excluded.value,
+                    payload = excluded.payload,
+                    source = excluded.source,
+                    last_updated = excluded.last_updated",
+            )?;
+            for row in rows {
+                let Some(item) = row.item() else {
+                    continue;
+                };
+                let payload = serde_json::to_string(row)?;
+                stmt.execute(params![
+                    row.symbol, item, row.date, row.value, payload, source, now
+                ])?;
+                n += 1;
+            }
+        }
+        log_fetch(&tx, source, n, &now)?;
+        tx.commit()?;
+        Ok(n)
+    }
+
+    /// Returns the stored line items of the `ticker:country` company.
+    pub fn financial_items(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<FinancialItem>, Box<dyn Error + Send + Sync>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT payload FROM financial_items WHERE symbol = ?1 ORDER BY item")?;
+        let payloads = stmt
+            .query_map(params![symbol], |r| r.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut items = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            items.push(serde_json::from_str(&payload)?);
+        }
+        Ok(items)
+    }
+
+    /// When rows from `source` were last written, if ever.
+    pub fn last_updated(
+        &self,
+        source: &str,
+    ) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        let ts = self
+            .conn
+            .query_row(
+                "SELECT last_updated FROM fetch_log WHERE source = ?1",
+                params![source],
+                |r| r.get(0),
+            )
+            .optional()?;
+        Ok(ts)
+    }
+}
+
+/// `/markets/historical` and `/ratings/historical` report dates as
+/// `dd/mm/yyyy`; store them as ISO so they sort and compare correctly.
+fn iso_date(date: &str) -> String {
+    match parse_date(date) {
+        Some(d) => d.format("%Y-%m-%d").to_string(),
+        None => date.to_string(),
+    }
+}
+
+fn log_fetch(conn: &Connection, source: &str, rows: usize, now: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO fetch_log (source, rows, last_updated) VALUES (?1, ?2, ?3)
+         ON CONFLICT (source) DO UPDATE SET rows = excluded.rows, last_updated = excluded.last_updated",
+        params![source, rows as i64, now],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,340 @@
+//! Date-typed time series with a declared frequency.
+//!
+//! Indicator histories come back at very different frequencies (monthly
+//! inflation, quarterly GDP, daily market closes). `TimeSeries` keeps the
+//! observations sorted by date together with their frequency so they can be
+//! resampled, joined as-of, and aligned onto a common calendar before being
+//! compared.
+
+use crate::indicators::HistoricalData;
+use crate::markets::MarketBar;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Frequency {
+    /// The last calendar day of the period containing `date`. Weeks end on
+    /// Sunday.
+    pub fn period_end(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Frequency::Daily => date,
+            Frequency::Weekly => {
+                date + Duration::days(6 - date.weekday().num_days_from_monday() as i64)
+            }
+            Frequency::Monthly => month_end(date.year(), date.month()),
+            Frequency::Quarterly => month_end(date.year(), ((date.month() - 1) / 3 + 1) * 3),
+            Frequency::Yearly => month_end(date.year(), 12),
+        }
+    }
+
+    /// Guesses the frequency of sorted dates from their median spacing, for
+    /// sources that do not declare one.
+    pub fn infer(dates: &[NaiveDate]) -> Option<Frequency> {
+        let mut gaps: Vec<i64> = dates.windows(2).map(|w| (w[1] - w[0]).num_days()).collect();
+        if gaps.is_empty() {
+            return None;
+        }
+        gaps.sort_unstable();
+        Some(match gaps[gaps.len() / 2] {
+            0..=4 => Frequency::Daily,
+            5..=10 => Frequency::Weekly,
+            11..=45 => Frequency::Monthly,
+            46..=135 => Frequency::Quarterly,
+            _ => Frequency::Yearly,
+        })
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = String;
+
+    /// Parses the `Frequency` field of the indicator metadata.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "daily" | "d" => Ok(Frequency::Daily),
+            "weekly" | "w" => Ok(Frequency::Weekly),
+            "monthly" | "m" => Ok(Frequency::Monthly),
+            "quarterly" | "q" => Ok(Frequency::Quarterly),
+            "yearly" | "annual" | "annually" | "y" => Ok(Frequency::Yearly),
+            other => Err(format!("unknown frequency: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Frequency::Daily => "Daily",
+            Frequency::Weekly => "Weekly",
+            Frequency::Monthly => "Monthly",
+            Frequency::Quarterly => "Quarterly",
+            Frequency::Yearly => "Yearly",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How observations falling in the same period are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resample {
+    Last,
+    Mean,
+    Sum,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeries {
+    pub name: String,
+    pub frequency: Frequency,
+    points: Vec<(NaiveDate, f64)>,
+}
+
+impl TimeSeries {
+    /// Builds a series from unordered points; on duplicate dates the last
+    /// point given wins.
+    pub fn new(name: &str, frequency: Frequency, points: Vec<(NaiveDate, f64)>) -> TimeSeries {
+        let map: BTreeMap<NaiveDate, f64> = points.into_iter().collect();
+        TimeSeries {
+            name: name.to_string(),
+            frequency,
+            points: map.into_iter().collect(),
+        }
+    }
+
+    /// Builds a series from `/historical/country/...` rows, taking the
+    /// frequency from the rows' metadata. Rows without a value are skipped.
+    pub fn from_historical(
+        rows: &[HistoricalData],
+    ) -> Result<TimeSeries, Box<dyn Error + Send + Sync>> {
+        let first = rows.first().ok_or("no historical rows")?;
+        let frequency = first
+            .frequency
+            .as_deref()
+            .ok_or("historical rows carry no frequency")?
+            .parse::<Frequency>()?;
+        let mut points = Vec::with_capacity(rows.len());
+        for row in rows {
+            let date = parse_date(&row.date_time)
+                .ok_or_else(|| format!("unparseable date: {}", row.date_time))?;
+            if let Some(value) = row.value {
+                points.push((date, value));
+            }
+        }
+        let name = format!("{} {}", first.country, first.category);
+        Ok(TimeSeries::new(&name, frequency, points))
+    }
+
+    /// Builds a daily series of closing prices. Bars without a close are
+    /// skipped.
+    pub fn from_market_bars(
+        bars: &[MarketBar],
+    ) -> Result<TimeSeries, Box<dyn Error + Send + Sync>> {
+        let first = bars.first().ok_or("no market bars")?;
+        let mut points = Vec::with_capacity(bars.len());
+        for bar in bars {
+            let date =
+                parse_date(&bar.date).ok_or_else(|| format!("unparseable date: {}", bar.date))?;
+            if let Some(close) = bar.close {
+                points.push((date, close));
+            }
+        }
+        Ok(TimeSeries::new(&first.symbol, Frequency::Daily, points))
+    }
+
+    pub fn points(&self) -> &[(NaiveDate, f64)] {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn first(&self) -> Option<(NaiveDate, f64)> {
+        self.points.first().copied()
+    }
+
+    pub fn last(&self) -> Option<(NaiveDate, f64)> {
+        self.points.last().copied()
+    }
+
+    pub fn get(&self, date: NaiveDate) -> Option<f64> {
+        self.points
+            .binary_search_by_key(&date, |p| p.0)
+            .ok()
+            .map(|i| self.points[i].1)
+    }
+
+    /// The latest observation on or before `date`.
+    pub fn asof(&self, date: NaiveDate) -> Option<(NaiveDate, f64)> {
+        let idx = self.points.partition_point(|p| p.0 <= date);
+        if idx == 0 {
+            None
+        } else {
+            Some(self.points[idx - 1])
+        }
+    }
+
+    /// Restricts the series to `[from, to]`.
+    pub fn between(&self, from: NaiveDate, to: NaiveDate) -> TimeSeries {
+        TimeSeries {
+            name: self.name.clone(),
+            frequency: self.frequency,
+            points: self
+                .points
+                .iter()
+                .filter(|p| p.0 >= from && p.0 <= to)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Buckets observations into periods of `frequency`, dated at the end of
+    /// each period, combining them with `how`.
+    pub fn resample(&self, frequency: Frequency, how: Resample) -> TimeSeries {
+        let mut buckets: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+        for &(date, value) in &self.points {
+            buckets
+                .entry(frequency.period_end(date))
+                .or_default()
+                .push(value);
+        }
+        let points = buckets
+            .into_iter()
+            .map(|(date, values)| {
+                let value = match how {
+                    Resample::Last => *values.last().unwrap(),
+                    Resample::Sum => values.iter().sum(),
+                    Resample::Mean => values.iter().sum::<f64>() / values.len() as f64,
+                };
+                (date, value)
+            })
+            .collect();
+        TimeSeries {
+            name: self.name.clone(),
+            frequency,
+            points,
+        }
+    }
+
+    /// For every date in `self`, pairs its value with the latest value of
+    /// `other` on or before that date.
+    pub fn asof_join(&self, other: &TimeSeries) -> Vec<(NaiveDate, f64, Option<f64>)> {
+        self.points
+            .iter()
+            .map(|&(date, value)| (date, value, other.asof(date).map(|p| p.1)))
+            .collect()
+    }
+
+    /// Point-by-point transformation keeping dates and frequency.
+    pub fn map<F: Fn(f64) -> f64>(&self, f: F) -> TimeSeries {
+        TimeSeries {
+            name: self.name.clone(),
+            frequency: self.frequency,
+            points: self.points.iter().map(|&(d, v)| (d, f(v))).collect(),
+        }
+    }
+}
+
+/// Several series sharing one calendar; missing observations are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignedSeries {
+    pub frequency: Frequency,
+    pub dates: Vec<NaiveDate>,
+    pub columns: Vec<(String, Vec<Option<f64>>)>,
+}
+
+impl AlignedSeries {
+    /// Rows where every series has a value.
+    pub fn complete_rows(&self) -> Vec<(NaiveDate, Vec<f64>)> {
+        let mut rows = Vec::new();
+        for (i, date) in self.dates.iter().enumerate() {
+            let values: Option<Vec<f64>> = self.columns.iter().map(|(_, col)| col[i]).collect();
+            if let Some(values) = values {
+                rows.push((*date, values));
+            }
+        }
+        rows
+    }
+
+    /// Carries the last known value of each column forward over gaps.
+    pub fn fill_forward(&mut self) {
+        for (_, col) in self.columns.iter_mut() {
+            let mut last = None;
+            for v in col.iter_mut() {
+                match v {
+                    Some(x) => last = Some(*x),
+                    None => *v = last,
+                }
+            }
+        }
+    }
+}
+
+/// Resamples every series to `frequency` and lays them out over the union of
+/// their period-end dates.
+pub fn align(series: &[TimeSeries], frequency: Frequency, how: Resample) -> AlignedSeries {
+    let resampled: Vec<TimeSeries> = series.iter().map(|s| s.resample(frequency, how)).collect();
+    let mut dates: Vec<NaiveDate> = resampled
+        .iter()
+        .flat_map(|s| s.points.iter().map(|p| p.0))
+        .collect();
+    dates.sort();
+    dates.dedup();
+    let columns = resampled
+        .iter()
+        .map(|s| (s.name.clone(), dates.iter().map(|&d| s.get(d)).collect()))
+        .collect();
+    AlignedSeries {
+        frequency,
+        dates,
+        columns,
+    }
+}
+
+/// Parses the date formats used across the API: ISO dates and datetimes
+/// (`2020-12-31T00:00:00`) and the `dd/mm/yyyy` form of market histories.
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    let s = s.trim();
+    if let Some(head) = s.get(..10) {
+        if let Ok(d) = NaiveDate::parse_from_str(head, "%Y-%m-%d") {
+            return Some(d);
+        }
+    }
+    NaiveDate::parse_from_str(s, "%d/%m/%Y").ok()
+}
+
+/// Reads an API timestamp such as `2024-05-01T12:30:00`, with optional
+/// fractional seconds, or a bare date at midnight. The API sends them
+/// without an offset and they are UTC.
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim().trim_end_matches('Z');
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .or_else(|| parse_date(s).and_then(|d| d.and_hms_opt(0, 0, 0)))?;
+    Some(Utc.from_utc_datetime(&naive))
+}
+
+fn month_end(year: i32, month: u32) -> NaiveDate {
+    let (y, m) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(y, m, 1).unwrap() - Duration::days(1)
+}
@@ -0,0 +1,320 @@
+//! World Bank indicators published through `/worldBank/...`.
+//!
+//! Indicator metadata is keyed by the bare World Bank code (`fr.inr.rinr`),
+//! while histories are keyed by that code prefixed with the country's
+//! lowercase ISO3 code (`usa.fr.inr.rinr`). `WbSymbol` builds the latter from
+//! a `Country` so callers no longer splice the two together by hand.
+
+use crate::api::{encode, get_json};
+use crate::timeseries::{align, parse_date, AlignedSeries, Frequency, Resample, TimeSeries};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// One entry of `/worldBank/categories`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WbCategory {
+    #[serde(alias = "Category", alias = "Name", alias = "name")]
+    pub category: String,
+}
+
+/// One row of a category or country listing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WbSeries {
+    #[serde(alias = "symbol")]
+    pub symbol: String,
+    #[serde(default, alias = "Title", alias = "title")]
+    pub name: Option<String>,
+    #[serde(default, alias = "country")]
+    pub country: Option<String>,
+    #[serde(default, alias = "category")]
+    pub category: Option<String>,
+    #[serde(default, alias = "frequency")]
+    pub frequency: Option<String>,
+    #[serde(default, alias = "Last", alias = "Value")]
+    pub latest_value: Option<f64>,
+    #[serde(default)]
+    pub latest_value_date: Option<String>,
+    #[serde(default, rename = "URL", alias = "url")]
+    pub url: Option<String>,
+    #[serde(default, alias = "lastUpdate")]
+    pub last_update: Option<String>,
+}
+
+/// Metadata returned by `/worldBank/indicator`. The payload carries both a
+/// long and a short definition and both a source and its organization, so
+/// each key has its own field; `description` and `source` pick one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WbIndicator {
+    #[serde(alias = "symbol")]
+    pub symbol: String,
+    #[serde(default, alias = "title")]
+    pub title: Option<String>,
+    #[serde(default, alias = "category")]
+    pub category: Option<String>,
+    #[serde(default, alias = "source")]
+    pub source: Option<String>,
+    #[serde(default, alias = "sourceOrganization")]
+    pub source_organization: Option<String>,
+    #[serde(default, alias = "longDefinition")]
+    pub long_definition: Option<String>,
+    #[serde(default, alias = "shortDefinition")]
+    pub short_definition: Option<String>,
+    #[serde(default, rename = "UnitOfMeasure", alias = "unitOfMeasure")]
+    pub unit: Option<String>,
+    #[serde(default, alias = "periodicity")]
+    pub periodicity: Option<String>,
+    #[serde(default, rename = "URL", alias = "url")]
+    pub url: Option<String>,
+    #[serde(default, alias = "lastUpdate")]
+    pub last_update: Option<String>,
+}
+
+impl WbIndicator {
+    /// The long definition, or the short one when the long one is blank.
+    pub fn description(&self) -> Option<&str> {
+        non_blank(&self.long_definition).or_else(|| non_blank(&self.short_definition))
+    }
+
+    /// The source, or the organization behind it when the source is blank.
+    pub fn source(&self) -> Option<&str> {
+        non_blank(&self.source).or_else(|| non_blank(&self.source_organization))
+    }
+
+    /// The periodicity as a `Frequency`, when it is one the series model
+    /// knows.
+    pub fn frequency(&self) -> Option<Frequency> {
+        self.periodicity.as_deref()?.parse().ok()
+    }
+}
+
+/// One observation from `/worldBank/historical`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WbHistorical {
+    #[serde(alias = "symbol")]
+    pub symbol: String,
+    #[serde(alias = "DateTime", alias = "date")]
+    pub date: String,
+    #[serde(default, alias = "value")]
+    pub value: Option<f64>,
+}
+
+/// A country as the World Bank dataset identifies it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Country {
+    pub name: String,
+    /// Lowercase ISO3 code, the prefix of the country's historical symbols.
+    pub iso3: String,
+}
+
+impl Country {
+    pub fn new(name: &str, iso3: &str) -> Result<Country, String> {
+        if iso3.len() != 3 || !iso3.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(format!("invalid ISO3 country code: {}", iso3));
+        }
+        Ok(Country {
+            name: name.to_string(),
+            iso3: iso3.to_lowercase(),
+        })
+    }
+
+    /// Looks the ISO3 code up from the first page of the country's listing,
+    /// whose symbols all carry it as a prefix.
+    pub fn lookup(
+        base_url: String,
+        client_key: String,
+        name: &str,
+    ) -> Result<Country, Box<dyn Error + Send + Sync>> {
+        let rows = get_wb_country(base_url, client_key, name, None)?;
+        let symbol = rows
+            .iter()
+            .find_map(|r| r.symbol.parse::<WbSymbol>().ok())
+            .ok_or_else(|| format!("no world bank series for country: {}", name))?;
+        Ok(Country {
+            name: name.to_string(),
+            iso3: symbol.country,
+        })
+    }
+}
+
+/// A historical series symbol: country ISO3 code plus indicator code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WbSymbol {
+    pub country: String,
+    pub indicator: String,
+}
+
+impl WbSymbol {
+    pub fn new(country: &Country, indicator: &str) -> Result<WbSymbol, String> {
+        let indicator = indicator.trim().to_lowercase();
+        if indicator.is_empty() || indicator.starts_with('.') || indicator.ends_with('.') {
+            return Err(format!("invalid world bank indicator code: {}", indicator));
+        }
+        Ok(WbSymbol {
+            country: country.iso3.clone(),
+            indicator,
+        })
+    }
+}
+
+impl FromStr for WbSymbol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.split_once('.') {
+            Some((country, indicator))
+                if country.len() == 3
+                    && country.bytes().all(|b| b.is_ascii_alphabetic())
+                    && !indicator.is_empty() =>
+            {
+                Ok(WbSymbol {
+                    country: country.to_string(),
+                    indicator: indicator.to_string(),
+                })
+            }
+            _ => Err(format!("invalid world bank symbol: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for WbSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.country, self.indicator)
+    }
+}
+
+pub fn get_wb_categories(
+    base_url: String,
+    client_key: String,
+) -> Result<Vec<WbCategory>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/worldBank/categories");
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Series in one category. Pages start at 1; `None` asks for the first page.
+pub fn get_wb_category(
+    base_url: String,
+    client_key: String,
+    category: &str,
+    page: Option<u32>,
+) -> Result<Vec<WbSeries>, Box<dyn Error + Send + Sync>> {
+    let mut path = format!("/worldBank/category/{}", encode(category));
+    if let Some(page) = page {
+        path = format!("{}/{}", path, page);
+    }
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+pub fn get_wb_country(
+    base_url: String,
+    client_key: String,
+    country: &str,
+    page: Option<u32>,
+) -> Result<Vec<WbSeries>, Box<dyn Error + Send + Sync>> {
+    let mut path = format!("/worldBank/country/{}", encode(country));
+    if let Some(page) = page {
+        path = format!("{}/{}", path, page);
+    }
+    let url = format!("{}{}?c={}&f=json", base_url, path, client_key);
+    get_json(url)
+}
+
+/// Metadata for an indicator code (`fr.inr.rinr`) or a full symbol.
+pub fn get_wb_indicator(
+    base_url: String,
+    client_key: String,
+    series_code: &str,
+) -> Result<Vec<WbIndicator>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/worldBank/indicator");
+    let url = format!(
+        "{}{}?s={}&c={}&f=json",
+        base_url,
+        path,
+        encode(series_code),
+        client_key
+    );
+    get_json(url)
+}
+
+/// Metadata for the series behind a site path such as
+/// `/united-states/real-interest-rate-percent-wb-data.html`.
+pub fn get_wb_indicator_url(
+    base_url: String,
+    client_key: String,
+    site_url: &str,
+) -> Result<Vec<WbIndicator>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/worldBank/indicator");
+    let url = format!(
+        "{}{}?url={}&c={}&f=json",
+        base_url,
+        path,
+        encode(site_url),
+        client_key
+    );
+    get_json(url)
+}
+
+pub fn get_wb_historical(
+    base_url: String,
+    client_key: String,
+    symbol: &WbSymbol,
+) -> Result<Vec<WbHistorical>, Box<dyn Error + Send + Sync>> {
+    let path = String::from("/worldBank/historical");
+    let url = format!(
+        "{}{}?s={}&c={}&f=json",
+        base_url,
+        path,
+        encode(&symbol.to_string()),
+        client_key
+    );
+    get_json(url)
+}
+
+/// Fetches one indicator for every country and aligns the histories, one
+/// column per country name. The frequency comes from the indicator's
+/// periodicity, falling back to the spacing of the observations.
+pub fn get_wb_histories(
+    base_url: String,
+    client_key: String,
+    countries: &[Country],
+    indicator: &str,
+) -> Result<AlignedSeries, Box<dyn Error + Send + Sync>> {
+    let declared = get_wb_indicator(base_url.clone(), client_key.clone(), indicator)?
+        .first()
+        .and_then(WbIndicator::frequency);
+    let mut series = Vec::with_capacity(countries.len());
+    for country in countries {
+        let symbol = WbSymbol::new(country, indicator)?;
+        let rows = get_wb_historical(base_url.clone(), client_key.clone(), &symbol)?;
+        let points = rows
+            .iter()
+            .filter_map(|r| Some((parse_date(&r.date)?, r.value?)))
+            .collect();
+        series.push((country.name.clone(), points));
+    }
+    let frequency = declared
+        .or_else(|| {
+            series.iter().find_map(|(_, points): &(String, Vec<_>)| {
+                let mut dates: Vec<_> = points.iter().map(|p| p.0).collect();
+                dates.sort();
+                Frequency::infer(&dates)
+            })
+        })
+        .unwrap_or(Frequency::Yearly);
+    let series: Vec<TimeSeries> = series
+        .into_iter()
+        .map(|(name, points)| TimeSeries::new(&name, frequency, points))
+        .collect();
+    Ok(align(&series, frequency, Resample::Last))
+}
+
+fn non_blank(s: &Option<String>) -> Option<&str> {
+    s.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}